@@ -0,0 +1,594 @@
+// Command-line interface: subcommands, one-off duration overrides and the
+// commands that run without opening the main window.
+
+use chrono::{Local, NaiveDate};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::config::Config;
+use crate::control::{self, Request, Response};
+use crate::export::{self, ExportFilter, ExportFormat};
+use crate::goals::{GoalHistory, GoalTracker};
+use crate::history::{InterruptionKind, SessionHistory};
+use crate::import::{self, ColumnMap, ImportFormat};
+use crate::resume::SavedSession;
+use crate::stats::Stats;
+use crate::statusbar::{self, StatusFormat};
+use crate::tasks::TaskList;
+use crate::timer::TimerStatus;
+
+#[derive(Parser)]
+#[command(name = "rust_pomodoro", version, about = "A Pomodoro timer")]
+pub struct Cli {
+    /// Run in the terminal without opening a window
+    #[arg(long)]
+    pub headless: bool,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Start the timer right away, optionally with one-off durations
+    Start {
+        #[command(flatten)]
+        overrides: Overrides,
+
+        /// Run in the terminal without opening a window
+        #[arg(long)]
+        headless: bool,
+    },
+    /// Pause the running timer, or the saved session if none is running
+    Pause,
+    /// Move the running timer on to the next session
+    Skip,
+    /// Show the running timer, or the saved session if none is running
+    Status {
+        /// Keep printing an update every second, e.g. for a status bar
+        #[arg(long)]
+        follow: bool,
+
+        /// Output format for status bars (plain when following)
+        #[arg(long, value_enum)]
+        format: Option<StatusFormat>,
+    },
+    /// Show statistics from the session history
+    Stats,
+    /// Write the session history as CSV, JSON Lines or iCalendar
+    Export {
+        /// Output format (default: from the output file's extension, else CSV)
+        #[arg(long, value_enum)]
+        format: Option<ExportFormat>,
+
+        /// First day to include (YYYY-MM-DD)
+        #[arg(long, value_name = "DATE")]
+        from: Option<NaiveDate>,
+
+        /// Last day to include (YYYY-MM-DD)
+        #[arg(long, value_name = "DATE")]
+        to: Option<NaiveDate>,
+
+        /// Only sessions spent on this task
+        #[arg(long)]
+        task: Option<String>,
+
+        /// File to write instead of standard output
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<PathBuf>,
+    },
+    /// Add sessions exported from another Pomodoro app to the history
+    Import {
+        /// CSV or JSON file to read
+        file: PathBuf,
+
+        /// Input format (default: from the file's extension)
+        #[arg(long, value_enum)]
+        format: Option<ImportFormat>,
+
+        #[command(flatten)]
+        columns: ColumnMap,
+
+        /// Report what would be imported without changing the history
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Open the full-screen terminal interface
+    Tui,
+    /// Play the tomato overlay animation
+    Overlay {
+        /// Text shown in the middle of the screen while the tomatoes fall
+        #[arg(long)]
+        caption: Option<String>,
+    },
+    /// Read or change persistent settings
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
+    /// Control the running timer through its local socket
+    Ctl {
+        #[command(subcommand)]
+        request: CtlCommand,
+
+        /// Print the raw JSON response
+        #[arg(long, global = true)]
+        json: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum CtlCommand {
+    /// Start or resume the current session
+    Start,
+    /// Pause the current session
+    Pause,
+    /// Restart the current session from the beginning
+    Reset,
+    /// Move on to the next session
+    Skip,
+    /// Print the current session
+    Status,
+    /// Add minutes to the current session
+    Extend {
+        #[arg(value_name = "MIN", value_parser = clap::value_parser!(u32).range(1..))]
+        minutes: u32,
+    },
+    /// Take minutes off the current session
+    Shorten {
+        #[arg(value_name = "MIN", value_parser = clap::value_parser!(u32).range(1..))]
+        minutes: u32,
+    },
+    /// Log an interruption against the current work session
+    Interrupt {
+        #[arg(value_enum)]
+        kind: InterruptionSource,
+        /// What interrupted you
+        #[arg(long)]
+        note: Option<String>,
+        /// Also pause the timer
+        #[arg(long)]
+        pause: bool,
+    },
+    /// Abandon the current work session; it won't count as a pomodoro
+    Void {
+        /// Why the session was abandoned
+        #[arg(long)]
+        reason: Option<String>,
+    },
+    /// Switch to the classic rotation with new lengths
+    SetDurations {
+        #[command(flatten)]
+        overrides: Overrides,
+    },
+}
+
+impl CtlCommand {
+    fn to_request(&self) -> Request {
+        match self {
+            CtlCommand::Start => Request::Start,
+            CtlCommand::Pause => Request::Pause,
+            CtlCommand::Reset => Request::Reset,
+            CtlCommand::Skip => Request::Skip,
+            CtlCommand::Status => Request::Status,
+            CtlCommand::Extend { minutes } => Request::Extend { minutes: *minutes },
+            CtlCommand::Shorten { minutes } => Request::Shorten { minutes: *minutes },
+            CtlCommand::Void { reason } => Request::Void { reason: reason.clone() },
+            CtlCommand::Interrupt { kind, note, pause } => Request::Interrupt {
+                kind: (*kind).into(),
+                note: note.clone(),
+                pause: *pause,
+            },
+            CtlCommand::SetDurations { overrides } => overrides.to_request(),
+        }
+    }
+}
+
+/// The kinds of interruption `ctl interrupt` accepts.
+#[derive(Clone, Copy, ValueEnum)]
+pub enum InterruptionSource {
+    /// The urge to do something else: check mail, fetch coffee, ...
+    Internal,
+    /// Someone or something else: a call, a colleague, ...
+    External,
+}
+
+impl From<InterruptionSource> for InterruptionKind {
+    fn from(source: InterruptionSource) -> Self {
+        match source {
+            InterruptionSource::Internal => InterruptionKind::Internal,
+            InterruptionSource::External => InterruptionKind::External,
+        }
+    }
+}
+
+#[derive(Subcommand)]
+pub enum ConfigAction {
+    /// Print one setting, or all of them
+    Get { key: Option<String> },
+    /// Change a setting, e.g. `config set work_minutes 50`
+    Set { key: String, value: String },
+    /// Print the location of the config file
+    Path,
+}
+
+/// Durations that apply to this run only and are not saved.
+#[derive(Args)]
+pub struct Overrides {
+    /// Work session length in minutes
+    #[arg(long, value_name = "MIN", value_parser = clap::value_parser!(u32).range(1..))]
+    pub work: Option<u32>,
+
+    /// Short break length in minutes
+    #[arg(long, value_name = "MIN", value_parser = clap::value_parser!(u32).range(1..))]
+    pub short: Option<u32>,
+
+    /// Long break length in minutes
+    #[arg(long, value_name = "MIN", value_parser = clap::value_parser!(u32).range(1..))]
+    pub long: Option<u32>,
+
+    /// Work sessions before each long break
+    #[arg(long, value_name = "COUNT", value_parser = clap::value_parser!(u32).range(1..))]
+    pub cycle: Option<u32>,
+}
+
+impl Overrides {
+    pub fn is_empty(&self) -> bool {
+        [self.work, self.short, self.long, self.cycle].iter().all(Option::is_none)
+    }
+
+    /// The same change for an instance that is already running.
+    pub fn to_request(&self) -> Request {
+        Request::SetDurations {
+            work: self.work,
+            short: self.short,
+            long: self.long,
+            cycle: self.cycle,
+        }
+    }
+
+    pub fn apply(&self, config: &mut Config) {
+        let overrides = [
+            (self.work, &mut config.work_minutes),
+            (self.short, &mut config.short_break_minutes),
+            (self.long, &mut config.long_break_minutes),
+            (self.cycle, &mut config.cycle_length),
+        ];
+
+        for (value, setting) in overrides {
+            if let Some(value) = value {
+                *setting = value;
+            }
+        }
+
+        // Explicit durations only make sense with the classic rotation
+        if !self.is_empty() {
+            config.custom_sequence = None;
+        }
+    }
+}
+
+/// On Windows release builds there is no console of our own; borrow the one
+/// we were started from so terminal output is visible.
+pub fn attach_parent_console() {
+    #[cfg(windows)]
+    unsafe {
+        winapi::um::wincon::AttachConsole(winapi::um::wincon::ATTACH_PARENT_PROCESS);
+    }
+}
+
+/// Runs a command that only prints to the terminal, then exits.
+pub fn run(command: Command) -> ! {
+    attach_parent_console();
+    let result = match command {
+        Command::Pause => pause(),
+        Command::Skip => ctl(&CtlCommand::Skip, false),
+        Command::Status { follow, format } => status(follow, format),
+        Command::Stats => stats(),
+        Command::Export { format, from, to, task, output } => {
+            export(format, ExportFilter { from, to, task }, output)
+        }
+        Command::Import { file, format, columns, dry_run } => import(&file, format, &columns, dry_run),
+        Command::Config { action } => config(action),
+        Command::Ctl { request, json } => ctl(&request, json),
+        Command::Start { .. } | Command::Tui | Command::Overlay { .. } => unreachable!("handled by main"),
+    };
+    exit_with(result)
+}
+
+/// Hands this launch's action to the instance that is already running, then exits.
+pub fn forward(requests: &[Request]) -> ! {
+    attach_parent_console();
+    exit_with(forward_all(requests))
+}
+
+fn exit_with(result: Result<(), String>) -> ! {
+    match result {
+        Ok(()) => std::process::exit(0),
+        Err(e) => {
+            eprintln!("error: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+// Sends a request to the running instance. `None` means nothing is listening.
+fn send_to_running(request: &Request) -> Option<Result<Response, String>> {
+    match control::send(request) {
+        Ok(response) => Some(Ok(response)),
+        Err(e) if matches!(e.kind(), io::ErrorKind::NotFound | io::ErrorKind::ConnectionRefused) => None,
+        Err(e) => Some(Err(format!("failed to reach the running timer: {}", e))),
+    }
+}
+
+fn print_status(status: &TimerStatus) {
+    let left = if status.overtime_secs.is_some() { "over" } else { "left" };
+    println!(
+        "{}: {} {} ({}), session {} of {}",
+        status.session_type.name(),
+        status.time,
+        left,
+        status.state(),
+        status.session,
+        status.cycle_length,
+    );
+    if let Some(task) = &status.task {
+        println!("Task: {}", task);
+    }
+    if status.interruptions > 0 {
+        println!("Interruptions: {}", status.interruptions);
+    }
+    if let Some(goal) = &status.goal {
+        println!("Goal: {}", goal.summary());
+    }
+}
+
+fn forward_all(requests: &[Request]) -> Result<(), String> {
+    let mut status = None;
+    for request in requests {
+        let response = send_to_running(request)
+            .ok_or("another instance is running but not answering on its control socket")??;
+        if !response.ok {
+            return Err(response.error.unwrap_or_else(|| "request failed".to_string()));
+        }
+        status = response.status;
+    }
+
+    println!("Already running; switched to the existing timer.");
+    if let Some(status) = status {
+        print_status(&status);
+    }
+    Ok(())
+}
+
+fn ctl(command: &CtlCommand, json: bool) -> Result<(), String> {
+    let response = send_to_running(&command.to_request())
+        .ok_or("no running timer (is the app started?)")??;
+    if json {
+        println!("{}", serde_json::to_string(&response).map_err(|e| e.to_string())?);
+    }
+    if !response.ok {
+        return Err(response.error.unwrap_or_else(|| "request failed".to_string()));
+    }
+    if let (false, Some(status)) = (json, &response.status) {
+        print_status(status);
+    }
+    Ok(())
+}
+
+fn status(follow: bool, format: Option<StatusFormat>) -> Result<(), String> {
+    if follow {
+        let format = format.unwrap_or(StatusFormat::Plain);
+        return match statusbar::follow(format) {
+            // The bar went away
+            Err(e) if e.kind() == io::ErrorKind::BrokenPipe => Ok(()),
+            result => result.map_err(|e| e.to_string()),
+        };
+    }
+    if let Some(format) = format {
+        println!("{}", statusbar::render(format, statusbar::current().as_ref()));
+        return Ok(());
+    }
+
+    if let Some(response) = send_to_running(&Request::Status) {
+        if let Some(status) = response?.status {
+            print_status(&status);
+        }
+        return Ok(());
+    }
+
+    let Some(saved) = SavedSession::load() else {
+        println!("No session in progress.");
+        return Ok(());
+    };
+
+    let snapshot = &saved.timer;
+    let mut remaining = snapshot.time_remaining;
+    if snapshot.is_running {
+        remaining = remaining.saturating_sub(saved.time_away());
+    }
+    let sequence = Config::load().sequence();
+
+    println!(
+        "{}: {:02}:{:02} left ({}), session {} of {}",
+        snapshot.session_type.name(),
+        remaining.as_secs() / 60,
+        remaining.as_secs() % 60,
+        if snapshot.is_running { "running" } else { "paused" },
+        sequence.work_position(snapshot.step),
+        sequence.cycle_length(),
+    );
+    if let Some(task) = &snapshot.task {
+        println!("Task: {}", task.name);
+    }
+    Ok(())
+}
+
+fn pause() -> Result<(), String> {
+    if let Some(response) = send_to_running(&Request::Pause) {
+        response?;
+        println!("Paused.");
+        return Ok(());
+    }
+
+    let mut saved = SavedSession::load().ok_or("no session in progress")?;
+    if !saved.timer.is_running {
+        println!("The session is already paused.");
+        return Ok(());
+    }
+
+    saved.timer.time_remaining = saved.timer.time_remaining.saturating_sub(saved.time_away());
+    saved.timer.is_running = false;
+    saved.saved_at = Local::now();
+    saved.save().map_err(|e| format!("failed to save session state: {}", e))?;
+    println!("Paused.");
+    Ok(())
+}
+
+fn stats() -> Result<(), String> {
+    let history = SessionHistory::open_default().ok_or("no data directory")?;
+    let records = history
+        .records()
+        .map_err(|e| format!("failed to read session history: {}", e))?;
+    let today = Local::now().date_naive();
+    let stats = Stats::compute(&records, today);
+
+    println!("Pomodoros today:      {}", stats.pomodoros_today);
+    println!("Pomodoros this week:  {}", stats.pomodoros_this_week);
+    println!("Total focus time:     {} min", stats.total_focus_minutes);
+    println!("Longest streak:       {} days", stats.longest_streak_days);
+    match stats.completion_ratio() {
+        Some(ratio) => println!(
+            "Completed / skipped:  {} / {} ({:.0}%)",
+            stats.completed,
+            stats.skipped,
+            ratio * 100.0
+        ),
+        None => println!("Completed / skipped:  0 / 0"),
+    }
+    println!("Voided:               {}", stats.voided);
+    let config = Config::load();
+    let goals = GoalTracker::new(config.daily_goal, config.weekly_goal, &records);
+    if goals.is_set() {
+        println!("Goal:                 {}", goals.progress(today).summary());
+    }
+    if config.daily_goal > 0 {
+        println!("Daily goal streak:    {} days", GoalHistory::load().streak(&goals, today));
+    }
+    println!(
+        "Interruptions today:  {} internal, {} external",
+        stats.internal_interruptions_today, stats.external_interruptions_today
+    );
+
+    println!();
+    println!("Last {} days:", stats.last_days.len());
+    for (i, count) in stats.last_days.iter().enumerate() {
+        let day = today - chrono::Duration::days((stats.last_days.len() - 1 - i) as i64);
+        let interruptions = stats.interruptions_last_days[i];
+        let interruptions = if interruptions > 0 {
+            format!("  ({} interruptions)", interruptions)
+        } else {
+            String::new()
+        };
+        println!("  {}  {:>2} {}{}", day.format("%a %d"), count, "🍅".repeat(*count as usize), interruptions);
+    }
+    Ok(())
+}
+
+fn export(format: Option<ExportFormat>, filter: ExportFilter, output: Option<PathBuf>) -> Result<(), String> {
+    if let (Some(from), Some(to)) = (filter.from, filter.to) {
+        if from > to {
+            return Err(format!("--from {} is after --to {}", from, to));
+        }
+    }
+    let history = SessionHistory::open_default().ok_or("no data directory")?;
+    let records = filter
+        .records(&history)
+        .map_err(|e| format!("failed to read session history: {}", e))?;
+    let format = format
+        .or_else(|| output.as_deref().and_then(ExportFormat::from_path))
+        .unwrap_or(ExportFormat::Csv);
+
+    match output {
+        Some(path) => {
+            export::write_file(format, &records, &path)
+                .map_err(|e| format!("failed to write {}: {}", path.display(), e))?;
+            eprintln!("Exported to {}", path.display());
+        }
+        None => match export::write(format, &records, &mut io::stdout().lock()) {
+            // e.g. piped into `head`
+            Err(e) if e.kind() == io::ErrorKind::BrokenPipe => {}
+            result => result.map_err(|e| format!("failed to write export: {}", e))?,
+        },
+    }
+    Ok(())
+}
+
+fn import(file: &Path, format: Option<ImportFormat>, columns: &ColumnMap, dry_run: bool) -> Result<(), String> {
+    let format = format
+        .or_else(|| ImportFormat::from_path(file))
+        .ok_or("can't tell the format from the file name; pass --format")?;
+    let text = fs::read_to_string(file).map_err(|e| format!("failed to read {}: {}", file.display(), e))?;
+    let history = SessionHistory::open_default().ok_or("no data directory")?;
+    let existing = history
+        .records()
+        .map_err(|e| format!("failed to read session history: {}", e))?;
+
+    let report = import::parse(format, &text, columns, &existing, &TaskList::load());
+    for error in &report.errors {
+        eprintln!("Skipped {}", error);
+    }
+    if !dry_run {
+        history
+            .append_all(&report.records)
+            .map_err(|e| format!("failed to write session history: {}", e))?;
+        // A running timer counts the sessions towards its goals
+        match send_to_running(&Request::HistoryChanged) {
+            Some(Ok(response)) if !response.ok => eprintln!(
+                "The running timer didn't pick up the import: {}",
+                response.error.unwrap_or_default()
+            ),
+            Some(Err(e)) => eprintln!("The running timer didn't pick up the import: {}", e),
+            _ => {}
+        }
+    }
+    println!(
+        "{} {} sessions, {} already in the history, {} unreadable.",
+        if dry_run { "Would import" } else { "Imported" },
+        report.records.len(),
+        report.duplicates,
+        report.errors.len()
+    );
+    Ok(())
+}
+
+fn config(action: ConfigAction) -> Result<(), String> {
+    let mut config = Config::load();
+    match action {
+        ConfigAction::Get { key: Some(key) } => {
+            let value = config
+                .get_key(&key)
+                .filter(|_| key != "version")
+                .ok_or_else(|| format!("unknown setting '{}' (known: {})", key, Config::keys().join(", ")))?;
+            println!("{}", value);
+        }
+        ConfigAction::Get { key: None } => {
+            for key in Config::keys() {
+                println!("{} = {}", key, config.get_key(&key).unwrap_or_default());
+            }
+        }
+        ConfigAction::Set { key, value } => {
+            config.set_key(&key, &value)?;
+            config
+                .save()
+                .map_err(|e| format!("failed to save config: {}", e))?;
+        }
+        ConfigAction::Path => match Config::path() {
+            Some(path) => println!("{}", path.display()),
+            None => return Err("no config directory".to_string()),
+        },
+    }
+    Ok(())
+}
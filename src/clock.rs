@@ -0,0 +1,62 @@
+// Time sources for the timer.
+// SystemClock reads the real clocks; ManualClock only moves when told to,
+// which lets tests simulate whole sessions without sleeping.
+
+use chrono::{DateTime, Local};
+#[cfg(test)]
+use std::sync::{Arc, Mutex};
+#[cfg(test)]
+use std::time::Duration;
+use std::time::Instant;
+
+pub trait Clock: Send + Sync {
+    /// Monotonic time used for counting down.
+    fn now(&self) -> Instant;
+
+    /// Wall-clock time used for timestamps in the session history.
+    fn local_now(&self) -> DateTime<Local>;
+}
+
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn local_now(&self) -> DateTime<Local> {
+        Local::now()
+    }
+}
+
+#[cfg(test)]
+#[derive(Clone)]
+pub struct ManualClock {
+    now: Arc<Mutex<(Instant, DateTime<Local>)>>,
+}
+
+#[cfg(test)]
+impl ManualClock {
+    pub fn new() -> Self {
+        Self {
+            now: Arc::new(Mutex::new((Instant::now(), Local::now()))),
+        }
+    }
+
+    pub fn advance(&self, by: Duration) {
+        let mut now = self.now.lock().unwrap();
+        now.0 += by;
+        now.1 += chrono::Duration::from_std(by).unwrap();
+    }
+}
+
+#[cfg(test)]
+impl Clock for ManualClock {
+    fn now(&self) -> Instant {
+        self.now.lock().unwrap().0
+    }
+
+    fn local_now(&self) -> DateTime<Local> {
+        self.now.lock().unwrap().1
+    }
+}
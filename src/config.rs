@@ -0,0 +1,319 @@
+// Persistent user settings stored as JSON in the platform config directory
+// (e.g. ~/.config/rust-pomodoro/config.json on Linux).
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+use crate::paths;
+use crate::sequence::SessionSequence;
+
+pub const CONFIG_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub version: u32,
+    pub work_minutes: u32,
+    pub short_break_minutes: u32,
+    pub long_break_minutes: u32,
+    pub cycle_length: u32,
+    /// Overrides the classic rotation above when set, e.g. "W25 S5 W50 L20".
+    pub custom_sequence: Option<String>,
+    pub notifications_enabled: bool,
+    pub overlay_enabled: bool,
+    /// Start a break as soon as the work session before it ends; otherwise
+    /// the timer waits for Start.
+    pub auto_start_breaks: bool,
+    /// Start work as soon as a break ends.
+    pub auto_start_work: bool,
+    /// Let work sessions run on past zero as overtime until skipped.
+    pub flow_mode: bool,
+    /// Lengthen the break after a work session by its overtime.
+    pub overtime_to_break: bool,
+    /// Pomodoros to aim for each day and each week; 0 for no goal.
+    pub daily_goal: u32,
+    pub weekly_goal: u32,
+    /// Show a tray icon; closing the window then hides it to the tray. Off by
+    /// default: without a tray host the hidden window couldn't be got back.
+    pub tray_enabled: bool,
+    pub sound_enabled: bool,
+    /// Percent, 0 to 100.
+    pub sound_volume: u32,
+    /// Quiet ticking while a work session runs.
+    pub ticking_enabled: bool,
+    /// WAV or OGG files played instead of the bundled chime when a session
+    /// of that kind ends.
+    pub work_sound: Option<String>,
+    pub short_break_sound: Option<String>,
+    pub long_break_sound: Option<String>,
+    /// Settings this build doesn't know, e.g. from a newer version, kept so
+    /// that saving writes them back unchanged.
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, Value>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            version: CONFIG_VERSION,
+            work_minutes: 25,
+            short_break_minutes: 5,
+            long_break_minutes: 15,
+            cycle_length: 4,
+            custom_sequence: None,
+            notifications_enabled: true,
+            overlay_enabled: true,
+            auto_start_breaks: true,
+            auto_start_work: true,
+            flow_mode: false,
+            overtime_to_break: false,
+            daily_goal: 0,
+            weekly_goal: 0,
+            tray_enabled: false,
+            sound_enabled: true,
+            sound_volume: 70,
+            ticking_enabled: false,
+            work_sound: None,
+            short_break_sound: None,
+            long_break_sound: None,
+            extra: serde_json::Map::new(),
+        }
+    }
+}
+
+impl Config {
+    pub fn path() -> Option<PathBuf> {
+        paths::config_file("config.json")
+    }
+
+    /// Loads the config file, falling back to defaults if it is missing or unreadable.
+    pub fn load() -> Self {
+        let Some(path) = Self::path() else {
+            return Self::default();
+        };
+
+        match fs::read_to_string(&path) {
+            Ok(text) => Self::from_json(&text).unwrap_or_else(|e| {
+                eprintln!("Ignoring invalid config file {}: {}", path.display(), e);
+                Self::default()
+            }),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Self::default(),
+            Err(e) => {
+                eprintln!("Failed to read config file {}: {}", path.display(), e);
+                Self::default()
+            }
+        }
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let path = Self::path()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no config directory"))?;
+        let json = serde_json::to_string_pretty(self)?;
+        paths::write_atomic(&path, json.as_bytes())
+    }
+
+    /// Parses a config file of any known version. Missing fields take their
+    /// defaults; unknown fields and a newer version are kept for saving.
+    pub fn from_json(text: &str) -> serde_json::Result<Self> {
+        let value: Value = serde_json::from_str(text)?;
+        let value = migrate(value).map_err(serde::de::Error::custom)?;
+        let mut config: Config = serde_json::from_value(value)?;
+        config.repair();
+        if config.version > CONFIG_VERSION {
+            eprintln!(
+                "Config file version {} is newer than this build supports ({}); settings it doesn't know are left as they are",
+                config.version, CONFIG_VERSION
+            );
+        }
+        Ok(config)
+    }
+
+    // Puts settings the timer cannot run with, e.g. from a hand-edited file,
+    // back to their defaults
+    fn repair(&mut self) {
+        let defaults = Config::default();
+        for (name, value, default) in [
+            ("work_minutes", &mut self.work_minutes, defaults.work_minutes),
+            ("short_break_minutes", &mut self.short_break_minutes, defaults.short_break_minutes),
+            ("long_break_minutes", &mut self.long_break_minutes, defaults.long_break_minutes),
+            ("cycle_length", &mut self.cycle_length, defaults.cycle_length),
+        ] {
+            if *value == 0 {
+                eprintln!("Config setting {} must be at least 1; using {}", name, default);
+                *value = default;
+            }
+        }
+        if self.sound_volume > 100 {
+            eprintln!("Config setting sound_volume must be at most 100; using 100");
+            self.sound_volume = 100;
+        }
+    }
+
+    /// Rejects values the timer cannot run with.
+    pub fn validate(&self) -> Result<(), String> {
+        for (name, value) in [
+            ("work_minutes", self.work_minutes),
+            ("short_break_minutes", self.short_break_minutes),
+            ("long_break_minutes", self.long_break_minutes),
+            ("cycle_length", self.cycle_length),
+        ] {
+            if value == 0 {
+                return Err(format!("{} must be at least 1", name));
+            }
+        }
+        if let Some(custom) = &self.custom_sequence {
+            SessionSequence::parse(custom)?;
+        }
+        if self.sound_volume > 100 {
+            return Err("sound_volume must be between 0 and 100".to_string());
+        }
+        Ok(())
+    }
+
+    /// Names of the settings that can be read and changed by key.
+    pub fn keys() -> Vec<String> {
+        match serde_json::to_value(Config::default()) {
+            Ok(Value::Object(object)) => object.keys().filter(|key| *key != "version").cloned().collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    pub fn get_key(&self, key: &str) -> Option<String> {
+        let value = serde_json::to_value(self).ok()?;
+        match value.get(key)? {
+            Value::String(text) => Some(text.clone()),
+            other => Some(other.to_string()),
+        }
+    }
+
+    /// Sets one setting from its text form: numbers and booleans as written,
+    /// anything else as a string, and `null` to clear an optional setting.
+    pub fn set_key(&mut self, key: &str, value: &str) -> Result<(), String> {
+        if !Self::keys().iter().any(|known| known == key) {
+            return Err(format!("unknown setting '{}' (known: {})", key, Self::keys().join(", ")));
+        }
+
+        let value = serde_json::from_str(value).unwrap_or_else(|_| Value::String(value.to_string()));
+        let mut document = serde_json::to_value(&*self).map_err(|e| e.to_string())?;
+        document[key] = value;
+        let updated: Config = serde_json::from_value(document)
+            .map_err(|e| format!("invalid value for {}: {}", key, e))?;
+        updated.validate()?;
+
+        *self = updated;
+        Ok(())
+    }
+
+    pub fn sequence(&self) -> SessionSequence {
+        if let Some(custom) = &self.custom_sequence {
+            match SessionSequence::parse(custom) {
+                Ok(sequence) => return sequence,
+                Err(e) => eprintln!("Ignoring invalid custom sequence '{}': {}", custom, e),
+            }
+        }
+
+        SessionSequence::classic(
+            self.work_minutes,
+            self.short_break_minutes,
+            self.long_break_minutes,
+            self.cycle_length,
+        )
+    }
+}
+
+// Upgrades an older config document one version at a time.
+fn migrate(mut value: Value) -> Result<Value, String> {
+    let Some(object) = value.as_object_mut() else {
+        return Ok(value);
+    };
+
+    let mut version = object.get("version").and_then(Value::as_u64).unwrap_or(0);
+    while version < CONFIG_VERSION as u64 {
+        match version {
+            // Unversioned files predate the version field but share its layout
+            0 => {}
+            _ => return Err(format!("no migration from config version {}", version)),
+        }
+        version += 1;
+    }
+    object.insert("version".to_string(), Value::from(version));
+
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_fields_use_defaults() {
+        let config = Config::from_json(r#"{ "version": 1, "work_minutes": 50 }"#).unwrap();
+        assert_eq!(config.work_minutes, 50);
+        assert_eq!(config.short_break_minutes, 5);
+        assert!(config.notifications_enabled);
+    }
+
+    #[test]
+    fn unknown_fields_and_newer_versions_survive_a_save() {
+        let mut config = Config::from_json(r#"{ "version": 99, "cycle_length": 6, "theme": "dark" }"#).unwrap();
+        assert_eq!(config.cycle_length, 6);
+        config.set_key("work_minutes", "50").unwrap();
+        assert!(config.set_key("theme", "light").is_err());
+
+        let saved: Value = serde_json::to_value(&config).unwrap();
+        assert_eq!(saved["version"], 99);
+        assert_eq!(saved["theme"], "dark");
+        assert_eq!(saved["work_minutes"], 50);
+        assert_eq!(Config::from_json(&saved.to_string()).unwrap(), config);
+    }
+
+    #[test]
+    fn unversioned_files_are_migrated() {
+        let config = Config::from_json(r#"{ "long_break_minutes": 20 }"#).unwrap();
+        assert_eq!(config.version, CONFIG_VERSION);
+        assert_eq!(config.long_break_minutes, 20);
+    }
+
+    #[test]
+    fn settings_can_be_set_by_key() {
+        let mut config = Config::default();
+        config.set_key("work_minutes", "50").unwrap();
+        config.set_key("overlay_enabled", "false").unwrap();
+        config.set_key("custom_sequence", "W50 L10").unwrap();
+        assert_eq!(config.get_key("work_minutes").as_deref(), Some("50"));
+        assert_eq!(config.get_key("overlay_enabled").as_deref(), Some("false"));
+        assert_eq!(config.get_key("custom_sequence").as_deref(), Some("W50 L10"));
+
+        config.set_key("custom_sequence", "null").unwrap();
+        assert_eq!(config.custom_sequence, None);
+
+        assert!(config.set_key("work_minutes", "soon").is_err());
+        assert!(config.set_key("work_minutes", "0").is_err());
+        assert!(config.set_key("custom_sequence", "X5").is_err());
+        assert!(config.set_key("sound_volume", "150").is_err());
+        assert!(config.set_key("version", "2").is_err());
+        assert!(config.set_key("colour", "red").is_err());
+        assert_eq!(config.work_minutes, 50);
+    }
+
+    #[test]
+    fn custom_sequence_overrides_classic_rotation() {
+        let mut config = Config::default();
+        assert_eq!(config.sequence(), SessionSequence::classic(25, 5, 15, 4));
+
+        config.custom_sequence = Some("W50 L10".to_string());
+        assert_eq!(config.sequence().to_string(), "W50 L10");
+    }
+
+    #[test]
+    fn zero_durations_fall_back_to_defaults() {
+        let config = Config::from_json(r#"{ "version": 1, "work_minutes": 0, "cycle_length": 0, "long_break_minutes": 20 }"#).unwrap();
+        assert_eq!(config.work_minutes, 25);
+        assert_eq!(config.cycle_length, 4);
+        assert_eq!(config.long_break_minutes, 20);
+        assert!(config.validate().is_ok());
+    }
+}
@@ -0,0 +1,265 @@
+// Local control socket so scripts and window-manager key bindings can drive a
+// running timer. Requests and responses are single lines of JSON, e.g.
+//   {"command":"skip"}
+//   {"command":"set-durations","work":50,"short":10}
+
+use serde::{Deserialize, Serialize};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+#[cfg(unix)]
+use std::os::unix::net::{UnixListener, UnixStream};
+
+use crate::history::{InterruptionKind, SessionHistory};
+use crate::paths;
+use crate::sequence::SessionSequence;
+use crate::timer::{PomodoroTimer, SessionType, TimerStatus};
+
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "command", rename_all = "kebab-case")]
+pub enum Request {
+    Start,
+    Pause,
+    Reset,
+    Skip,
+    Status,
+    /// Brings the window to the front; the timer itself is unaffected.
+    Focus,
+    /// Adds minutes to the current session.
+    Extend { minutes: u32 },
+    /// Takes minutes off the current session.
+    Shorten { minutes: u32 },
+    /// Logs an interruption against the current work session.
+    Interrupt {
+        kind: InterruptionKind,
+        #[serde(default)]
+        note: Option<String>,
+        /// Also pause the timer for it.
+        #[serde(default)]
+        pause: bool,
+    },
+    /// Abandons the current work session without counting it.
+    Void {
+        #[serde(default)]
+        reason: Option<String>,
+    },
+    /// Rereads the session history after something else added to it, e.g. an
+    /// import, so goals it reached are announced.
+    HistoryChanged,
+    /// Switches to the classic rotation with these lengths in minutes;
+    /// missing values keep the current ones.
+    SetDurations {
+        #[serde(default)]
+        work: Option<u32>,
+        #[serde(default)]
+        short: Option<u32>,
+        #[serde(default)]
+        long: Option<u32>,
+        #[serde(default)]
+        cycle: Option<u32>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Response {
+    pub ok: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<TimerStatus>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl Response {
+    fn error(message: impl Into<String>) -> Self {
+        Self {
+            ok: false,
+            status: None,
+            error: Some(message.into()),
+        }
+    }
+}
+
+pub fn socket_path() -> PathBuf {
+    paths::runtime_file("sock")
+}
+
+/// Applies a request to the timer and reports its state afterwards.
+pub fn handle(timer: &Mutex<PomodoroTimer>, request: &Request) -> Response {
+    let mut timer = timer.lock().unwrap();
+    match *request {
+        Request::Start => timer.start(),
+        Request::Pause => timer.pause(),
+        Request::Reset => timer.reset(),
+        Request::Skip => timer.skip(),
+        Request::Status | Request::Focus => {}
+        Request::Extend { minutes } => timer.extend(Duration::from_secs(minutes as u64 * 60)),
+        Request::Shorten { minutes } => timer.shorten(Duration::from_secs(minutes as u64 * 60)),
+        Request::Void { ref reason } => {
+            if !timer.void(reason.clone()) {
+                return Response::error("no work session in progress");
+            }
+        }
+        Request::Interrupt { kind, ref note, pause } => {
+            if !timer.interrupt(kind, note.clone(), pause) {
+                return Response::error("no work session in progress");
+            }
+        }
+        Request::SetDurations { work, short, long, cycle } => {
+            let current = timer.sequence();
+            let minutes = |value: Option<u32>, session_type, default| {
+                value.or_else(|| current.minutes_of(session_type)).unwrap_or(default)
+            };
+            let values = [
+                minutes(work, SessionType::Work, 25),
+                minutes(short, SessionType::ShortBreak, 5),
+                minutes(long, SessionType::LongBreak, 15),
+                cycle.unwrap_or_else(|| current.cycle_length()),
+            ];
+            if values.contains(&0) {
+                return Response::error("durations and cycle length must be at least 1");
+            }
+            timer.set_sequence(SessionSequence::classic(values[0], values[1], values[2], values[3]));
+        }
+        Request::HistoryChanged => match SessionHistory::open_default().map(|history| history.records()) {
+            Some(Ok(records)) => timer.recount_goals(&records),
+            Some(Err(e)) => return Response::error(format!("failed to read session history: {}", e)),
+            None => return Response::error("no data directory"),
+        },
+    }
+
+    Response {
+        ok: true,
+        status: Some(timer.status()),
+        error: None,
+    }
+}
+
+/// Serves the control socket on a background thread until dropped.
+#[cfg(unix)]
+pub struct ControlServer {
+    path: PathBuf,
+}
+
+#[cfg(unix)]
+impl ControlServer {
+    /// `on_request` runs after every request, e.g. to repaint the window.
+    pub fn start(
+        timer: Arc<Mutex<PomodoroTimer>>,
+        on_request: impl Fn(&Request) + Send + Sync + 'static,
+    ) -> io::Result<Self> {
+        let path = socket_path();
+        let listener = match UnixListener::bind(&path) {
+            Ok(listener) => listener,
+            Err(e) if e.kind() == io::ErrorKind::AddrInUse => {
+                // A socket file left by a crashed process refuses connections
+                if UnixStream::connect(&path).is_ok() {
+                    return Err(e);
+                }
+                std::fs::remove_file(&path)?;
+                UnixListener::bind(&path)?
+            }
+            Err(e) => return Err(e),
+        };
+
+        let on_request = Arc::new(on_request);
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(stream) = stream else {
+                    continue;
+                };
+                let timer = timer.clone();
+                let on_request = on_request.clone();
+                std::thread::spawn(move || {
+                    if let Err(e) = serve_client(stream, &timer, &*on_request) {
+                        eprintln!("Control client error: {}", e);
+                    }
+                });
+            }
+        });
+
+        Ok(Self { path })
+    }
+}
+
+#[cfg(unix)]
+impl Drop for ControlServer {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+#[cfg(unix)]
+fn serve_client(stream: UnixStream, timer: &Mutex<PomodoroTimer>, on_request: &dyn Fn(&Request)) -> io::Result<()> {
+    stream.set_read_timeout(Some(CLIENT_TIMEOUT))?;
+    let mut writer = stream.try_clone()?;
+    for line in BufReader::new(stream).lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let response = match serde_json::from_str::<Request>(&line) {
+            Ok(request) => {
+                let response = handle(timer, &request);
+                on_request(&request);
+                response
+            }
+            Err(e) => Response::error(format!("invalid request: {}", e)),
+        };
+
+        let mut reply = serde_json::to_string(&response)?;
+        reply.push('\n');
+        writer.write_all(reply.as_bytes())?;
+    }
+    Ok(())
+}
+
+/// Sends one request to the running instance.
+#[cfg(unix)]
+pub fn send(request: &Request) -> io::Result<Response> {
+    let mut stream = UnixStream::connect(socket_path())?;
+    stream.set_read_timeout(Some(CLIENT_TIMEOUT))?;
+
+    let mut line = serde_json::to_string(request)?;
+    line.push('\n');
+    stream.write_all(line.as_bytes())?;
+    stream.shutdown(std::net::Shutdown::Write)?;
+
+    let mut reply = String::new();
+    BufReader::new(stream).read_line(&mut reply)?;
+    Ok(serde_json::from_str(&reply)?)
+}
+
+#[cfg(not(unix))]
+pub fn send(_request: &Request) -> io::Result<Response> {
+    Err(io::Error::new(io::ErrorKind::Unsupported, "the control socket is only available on Unix"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn requests_use_kebab_case_commands() {
+        let request: Request = serde_json::from_str(r#"{"command":"set-durations","work":50}"#).unwrap();
+        assert_eq!(request, Request::SetDurations { work: Some(50), short: None, long: None, cycle: None });
+        assert_eq!(serde_json::to_string(&Request::Skip).unwrap(), r#"{"command":"skip"}"#);
+        let request: Request = serde_json::from_str(r#"{"command":"interrupt","kind":"external"}"#).unwrap();
+        assert_eq!(request, Request::Interrupt { kind: InterruptionKind::External, note: None, pause: false });
+    }
+
+    #[test]
+    fn set_durations_keeps_unspecified_values() {
+        let timer = Mutex::new(PomodoroTimer::new());
+        let response = handle(&timer, &Request::SetDurations { work: Some(50), short: None, long: None, cycle: Some(2) });
+        assert!(response.ok);
+        assert_eq!(timer.lock().unwrap().sequence().to_string(), "W50 S5 W50 L15");
+
+        let response = handle(&timer, &Request::SetDurations { work: Some(0), short: None, long: None, cycle: None });
+        assert!(!response.ok);
+    }
+}
@@ -0,0 +1,297 @@
+// Session-bus service so desktop extensions and scripts can drive the timer:
+//   busctl --user call org.pomodoro.Timer /org/pomodoro/Timer org.pomodoro.Timer Skip
+// Methods Start/Pause/Reset/Skip and Extend/Shorten (in minutes), read-only
+// properties for the countdown and cycle position, and
+// SessionStarted/SessionCompleted signals.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::RecvTimeoutError;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use zbus::blocking::connection::Builder;
+use zbus::blocking::Connection;
+use zbus::interface;
+use zbus::object_server::SignalEmitter;
+
+use crate::history::SessionOutcome;
+use crate::timer::{PomodoroTimer, SessionType, TimerEvent, TimerStatus};
+
+pub const BUS_NAME: &str = "org.pomodoro.Timer";
+pub const OBJECT_PATH: &str = "/org/pomodoro/Timer";
+
+const POLL: Duration = Duration::from_secs(1);
+
+struct TimerInterface {
+    timer: Arc<Mutex<PomodoroTimer>>,
+    on_change: Arc<dyn Fn() + Send + Sync>,
+}
+
+impl TimerInterface {
+    fn control(&self, action: fn(&mut PomodoroTimer)) {
+        action(&mut self.timer.lock().unwrap());
+        (self.on_change)();
+    }
+
+    fn status(&self) -> TimerStatus {
+        self.timer.lock().unwrap().status()
+    }
+}
+
+#[interface(name = "org.pomodoro.Timer")]
+impl TimerInterface {
+    fn start(&self) {
+        self.control(PomodoroTimer::start);
+    }
+
+    fn pause(&self) {
+        self.control(PomodoroTimer::pause);
+    }
+
+    fn reset(&self) {
+        self.control(PomodoroTimer::reset);
+    }
+
+    fn skip(&self) {
+        self.control(PomodoroTimer::skip);
+    }
+
+    fn extend(&self, minutes: u32) {
+        self.timer.lock().unwrap().extend(Duration::from_secs(minutes as u64 * 60));
+        (self.on_change)();
+    }
+
+    fn shorten(&self, minutes: u32) {
+        self.timer.lock().unwrap().shorten(Duration::from_secs(minutes as u64 * 60));
+        (self.on_change)();
+    }
+
+    // The countdown changes every second, so clients poll it instead of
+    // being sent PropertiesChanged
+    #[zbus(property(emits_changed_signal = "false"))]
+    fn remaining_seconds(&self) -> u64 {
+        self.status().remaining_secs
+    }
+
+    #[zbus(property(emits_changed_signal = "false"))]
+    fn time_remaining(&self) -> String {
+        self.status().time
+    }
+
+    #[zbus(property)]
+    fn session_type(&self) -> String {
+        type_name(self.status().session_type).to_string()
+    }
+
+    /// Position of the current (or last) work session in the cycle, from 1.
+    #[zbus(property)]
+    fn cycle_index(&self) -> u32 {
+        self.status().session
+    }
+
+    #[zbus(property)]
+    fn cycle_length(&self) -> u32 {
+        self.status().cycle_length
+    }
+
+    #[zbus(property)]
+    fn running(&self) -> bool {
+        self.status().is_running
+    }
+
+    /// True when the last session ended and the next waits for Start.
+    #[zbus(property)]
+    fn waiting(&self) -> bool {
+        self.status().waiting
+    }
+
+    #[zbus(signal)]
+    async fn session_started(emitter: &SignalEmitter<'_>, session_type: &str) -> zbus::Result<()>;
+
+    /// Sent when a session runs to the end, not when it is skipped or reset.
+    #[zbus(signal)]
+    async fn session_completed(emitter: &SignalEmitter<'_>, session_type: &str) -> zbus::Result<()>;
+}
+
+fn type_name(session_type: SessionType) -> &'static str {
+    match session_type {
+        SessionType::Work => "Work",
+        SessionType::ShortBreak => "ShortBreak",
+        SessionType::LongBreak => "LongBreak",
+    }
+}
+
+/// Owns the bus name until dropped.
+pub struct DbusService {
+    _connection: Connection,
+    running: Arc<AtomicBool>,
+}
+
+impl DbusService {
+    /// `on_change` runs after every method call, e.g. to repaint the window.
+    pub fn start(
+        timer: Arc<Mutex<PomodoroTimer>>,
+        on_change: impl Fn() + Send + Sync + 'static,
+    ) -> zbus::Result<Self> {
+        Self::start_on(Builder::session()?, timer, on_change)
+    }
+
+    fn start_on(
+        builder: Builder<'_>,
+        timer: Arc<Mutex<PomodoroTimer>>,
+        on_change: impl Fn() + Send + Sync + 'static,
+    ) -> zbus::Result<Self> {
+        let events = timer.lock().unwrap().subscribe();
+        let interface = TimerInterface {
+            timer: timer.clone(),
+            on_change: Arc::new(on_change),
+        };
+        let connection = builder.name(BUS_NAME)?.serve_at(OBJECT_PATH, interface)?.build()?;
+        let interface = connection
+            .object_server()
+            .interface::<_, TimerInterface>(OBJECT_PATH)?;
+
+        // Relays timer events as signals and keeps the countdown moving even
+        // when no window is drawing it
+        let running = Arc::new(AtomicBool::new(true));
+        {
+            let running = running.clone();
+            std::thread::spawn(move || {
+                let emitter = interface.signal_emitter();
+                let mut last = timer.lock().unwrap().status();
+                while running.load(Ordering::SeqCst) {
+                    let sent = match events.recv_timeout(POLL) {
+                        Ok(TimerEvent::SessionStarted(session_type)) => zbus::block_on(
+                            TimerInterface::session_started(emitter, type_name(session_type)),
+                        ),
+                        Ok(TimerEvent::SessionFinished(record)) if record.outcome == SessionOutcome::Completed => {
+                            zbus::block_on(TimerInterface::session_completed(
+                                emitter,
+                                type_name(record.session_type),
+                            ))
+                        }
+                        Ok(_) | Err(RecvTimeoutError::Timeout) => Ok(()),
+                        Err(RecvTimeoutError::Disconnected) => break,
+                    };
+
+                    let status = timer.lock().unwrap().status();
+                    let iface = interface.get();
+                    let changed = zbus::block_on(async {
+                        if status.session_type != last.session_type {
+                            iface.session_type_changed(emitter).await?;
+                        }
+                        if status.session != last.session {
+                            iface.cycle_index_changed(emitter).await?;
+                        }
+                        if status.cycle_length != last.cycle_length {
+                            iface.cycle_length_changed(emitter).await?;
+                        }
+                        if status.is_running != last.is_running {
+                            iface.running_changed(emitter).await?;
+                        }
+                        if status.waiting != last.waiting {
+                            iface.waiting_changed(emitter).await?;
+                        }
+                        zbus::Result::Ok(())
+                    });
+                    drop(iface);
+                    last = status;
+
+                    if let Err(e) = sent.and(changed) {
+                        eprintln!("D-Bus signal failed: {}", e);
+                    }
+                }
+            });
+        }
+
+        Ok(Self {
+            _connection: connection,
+            running,
+        })
+    }
+}
+
+impl Drop for DbusService {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::SeqCst);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::ManualClock;
+    use std::io::{BufRead, BufReader};
+    use std::process::{Child, Command, Stdio};
+    use zbus::blocking::proxy::Builder as ProxyBuilder;
+    use zbus::blocking::Proxy;
+    use zbus::proxy::CacheProperties;
+
+    // A private bus so the test never touches the user's session bus
+    struct TestBus {
+        daemon: Child,
+        address: String,
+    }
+
+    impl TestBus {
+        fn start() -> Option<Self> {
+            let mut daemon = Command::new("dbus-daemon")
+                .args(["--session", "--nofork", "--print-address=1"])
+                .stdout(Stdio::piped())
+                .stderr(Stdio::null())
+                .spawn()
+                .ok()?;
+            let mut address = String::new();
+            BufReader::new(daemon.stdout.take()?).read_line(&mut address).ok()?;
+            Some(Self {
+                daemon,
+                address: address.trim().to_string(),
+            })
+        }
+    }
+
+    impl Drop for TestBus {
+        fn drop(&mut self) {
+            let _ = self.daemon.kill();
+            let _ = self.daemon.wait();
+        }
+    }
+
+    #[test]
+    fn serves_methods_properties_and_signals() {
+        let Some(bus) = TestBus::start() else {
+            eprintln!("dbus-daemon not found; skipping");
+            return;
+        };
+
+        let clock = ManualClock::new();
+        let timer = Arc::new(Mutex::new(PomodoroTimer::with_clock(Arc::new(clock.clone()))));
+        let _service = DbusService::start_on(Builder::address(bus.address.as_str()).unwrap(), timer.clone(), || {})
+            .unwrap();
+
+        let client = Builder::address(bus.address.as_str()).unwrap().build().unwrap();
+        let proxy: Proxy = ProxyBuilder::new(&client)
+            .destination(BUS_NAME)
+            .unwrap()
+            .path(OBJECT_PATH)
+            .unwrap()
+            .interface(BUS_NAME)
+            .unwrap()
+            .cache_properties(CacheProperties::No)
+            .build()
+            .unwrap();
+        let mut completed = proxy.receive_signal("SessionCompleted").unwrap();
+
+        let _: () = proxy.call("Start", &()).unwrap();
+        assert!(proxy.get_property::<bool>("Running").unwrap());
+        assert_eq!(proxy.get_property::<String>("SessionType").unwrap(), "Work");
+        assert_eq!(proxy.get_property::<u64>("RemainingSeconds").unwrap(), 25 * 60);
+
+        clock.advance(Duration::from_secs(25 * 60));
+        let signal = completed.next().unwrap();
+        let (session_type,): (String,) = signal.body().deserialize().unwrap();
+        assert_eq!(session_type, "Work");
+        assert_eq!(proxy.get_property::<String>("SessionType").unwrap(), "ShortBreak");
+        assert_eq!(proxy.get_property::<u32>("CycleIndex").unwrap(), 1);
+    }
+}
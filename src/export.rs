@@ -0,0 +1,276 @@
+// Session history export for spreadsheets and calendars: CSV, JSON, JSON
+// Lines (the history's own format) and iCalendar with one event per work
+// session.
+
+use chrono::{Local, NaiveDate, Utc};
+use clap::ValueEnum;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use crate::history::{SessionHistory, SessionOutcome, SessionRecord};
+use crate::timer::SessionType;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ExportFormat {
+    Csv,
+    /// A JSON array of records
+    Json,
+    /// One JSON record per line
+    Jsonl,
+    /// iCalendar, work sessions only
+    Ics,
+}
+
+impl ExportFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Json => "json",
+            ExportFormat::Jsonl => "jsonl",
+            ExportFormat::Ics => "ics",
+        }
+    }
+
+    /// The format a file name asks for, if its extension is known.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()?.to_ascii_lowercase().as_str() {
+            "csv" => Some(ExportFormat::Csv),
+            "json" => Some(ExportFormat::Json),
+            "jsonl" => Some(ExportFormat::Jsonl),
+            "ics" | "ical" => Some(ExportFormat::Ics),
+            _ => None,
+        }
+    }
+}
+
+/// Which records to export; every bound is optional and dates are inclusive.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ExportFilter {
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+    /// Task name, matched case-insensitively
+    pub task: Option<String>,
+}
+
+impl ExportFilter {
+    /// The history's records that pass the filter.
+    pub fn records(&self, history: &SessionHistory) -> io::Result<Vec<SessionRecord>> {
+        let first = self.from.unwrap_or(NaiveDate::MIN);
+        let last = self.to.unwrap_or(NaiveDate::MAX);
+        let mut records = history.on_dates(first, last)?;
+        if let Some(name) = &self.task {
+            records.retain(|record| record.task.as_ref().is_some_and(|task| task.name.eq_ignore_ascii_case(name)));
+        }
+        Ok(records)
+    }
+}
+
+/// Writes the records in the given format.
+pub fn write(format: ExportFormat, records: &[SessionRecord], out: &mut impl Write) -> io::Result<()> {
+    match format {
+        ExportFormat::Csv => write_csv(records.iter(), out),
+        ExportFormat::Json => {
+            serde_json::to_writer_pretty(&mut *out, records)?;
+            writeln!(out)
+        }
+        ExportFormat::Jsonl => {
+            for record in records {
+                writeln!(out, "{}", serde_json::to_string(record)?)?;
+            }
+            Ok(())
+        }
+        ExportFormat::Ics => write_ics(records.iter(), out),
+    }
+}
+
+/// Writes an export to `path`.
+pub fn write_file(format: ExportFormat, records: &[SessionRecord], path: &Path) -> io::Result<()> {
+    let mut out = BufWriter::new(File::create(path)?);
+    write(format, records, &mut out)?;
+    out.flush()
+}
+
+/// Where the window's export buttons save: the downloads folder (or home),
+/// named after today's date, numbered rather than overwrite an earlier export.
+pub fn default_path(format: ExportFormat) -> Option<PathBuf> {
+    let dir = dirs::download_dir().or_else(dirs::home_dir)?;
+    let stem = format!("pomodoro-history-{}", Local::now().format("%Y-%m-%d"));
+    (1..)
+        .map(|number| match number {
+            1 => dir.join(format!("{}.{}", stem, format.extension())),
+            _ => dir.join(format!("{}-{}.{}", stem, number, format.extension())),
+        })
+        .find(|path| !path.exists())
+}
+
+fn type_name(session_type: SessionType) -> &'static str {
+    match session_type {
+        SessionType::Work => "work",
+        SessionType::ShortBreak => "short_break",
+        SessionType::LongBreak => "long_break",
+    }
+}
+
+fn outcome_name(outcome: SessionOutcome) -> &'static str {
+    match outcome {
+        SessionOutcome::Completed => "completed",
+        SessionOutcome::Skipped => "skipped",
+        SessionOutcome::Reset => "reset",
+        SessionOutcome::Voided => "voided",
+    }
+}
+
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+fn write_csv<'a>(records: impl Iterator<Item = &'a SessionRecord>, out: &mut impl Write) -> io::Result<()> {
+    writeln!(
+        out,
+        "started_at,ended_at,session_type,outcome,planned_secs,focused_secs,overtime_secs,task,interruptions,void_reason"
+    )?;
+    for record in records {
+        writeln!(
+            out,
+            "{},{},{},{},{},{},{},{},{},{}",
+            record.started_at.to_rfc3339(),
+            record.ended_at.to_rfc3339(),
+            type_name(record.session_type),
+            outcome_name(record.outcome),
+            record.planned_secs,
+            record.focused_secs,
+            record.overtime_secs,
+            csv_field(record.task.as_ref().map_or("", |task| task.name.as_str())),
+            record.interruptions.len(),
+            csv_field(record.void_reason.as_deref().unwrap_or("")),
+        )?;
+    }
+    Ok(())
+}
+
+// RFC 5545 text: backslash-escape the separators and newlines
+fn ics_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+// Lines longer than 75 octets continue on the next line after a space
+fn ics_line(out: &mut impl Write, line: &str) -> io::Result<()> {
+    let mut rest = line;
+    let mut limit = 75;
+    while rest.len() > limit {
+        let mut split = limit;
+        while !rest.is_char_boundary(split) {
+            split -= 1;
+        }
+        write!(out, "{}\r\n ", &rest[..split])?;
+        rest = &rest[split..];
+        // The leading space counts towards the continuation's length
+        limit = 74;
+    }
+    write!(out, "{}\r\n", rest)
+}
+
+fn write_ics<'a>(records: impl Iterator<Item = &'a SessionRecord>, out: &mut impl Write) -> io::Result<()> {
+    let stamp = |time: chrono::DateTime<Local>| time.with_timezone(&Utc).format("%Y%m%dT%H%M%SZ").to_string();
+    let now = stamp(Local::now());
+
+    ics_line(out, "BEGIN:VCALENDAR")?;
+    ics_line(out, "VERSION:2.0")?;
+    ics_line(out, "PRODID:-//rust-pomodoro//Session history//EN")?;
+    for record in records.filter(|record| record.session_type == SessionType::Work) {
+        let summary = record.task.as_ref().map_or("Pomodoro", |task| task.name.as_str());
+        let mut description = format!("{} work session, {} min focused", outcome_name(record.outcome), record.focused_secs / 60);
+        if !record.interruptions.is_empty() {
+            description.push_str(&format!(", {} interruptions", record.interruptions.len()));
+        }
+
+        ics_line(out, "BEGIN:VEVENT")?;
+        ics_line(out, &format!("UID:{}@rust-pomodoro", record.started_at.timestamp_millis()))?;
+        ics_line(out, &format!("DTSTAMP:{}", now))?;
+        ics_line(out, &format!("DTSTART:{}", stamp(record.started_at)))?;
+        ics_line(out, &format!("DTEND:{}", stamp(record.ended_at)))?;
+        ics_line(out, &format!("SUMMARY:{}", ics_text(summary)))?;
+        ics_line(out, &format!("DESCRIPTION:{}", ics_text(&description)))?;
+        ics_line(out, "END:VEVENT")?;
+    }
+    ics_line(out, "END:VCALENDAR")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::history::SessionTask;
+    use chrono::TimeZone;
+
+    fn record(day: u32, session_type: SessionType, task: Option<&str>) -> SessionRecord {
+        SessionRecord {
+            session_type,
+            task: task.map(|name| SessionTask { id: 1, name: name.to_string() }),
+            ..SessionRecord::pomodoro(Local.with_ymd_and_hms(2024, 5, day, 10, 0, 0).unwrap())
+        }
+    }
+
+    fn export(format: ExportFormat, records: &[SessionRecord]) -> String {
+        let mut out = Vec::new();
+        write(format, records, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn filters_by_date_and_task() {
+        let path = std::env::temp_dir().join(format!("pomodoro-export-{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let history = SessionHistory::new(path.clone());
+        let records = [
+            record(1, SessionType::Work, Some("Report, draft")),
+            record(2, SessionType::Work, Some("Email")),
+            record(3, SessionType::Work, Some("report, draft")),
+        ];
+        history.append_all(&records).unwrap();
+        let filter = ExportFilter {
+            from: NaiveDate::from_ymd_opt(2024, 5, 2),
+            to: None,
+            task: Some("Report, Draft".to_string()),
+        };
+
+        let csv = export(ExportFormat::Csv, &filter.records(&history).unwrap());
+        let lines: Vec<_> = csv.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[1].contains(",work,completed,1500,1500,0,\"report, draft\",0,"));
+
+        let jsonl = export(ExportFormat::Jsonl, &ExportFilter::default().records(&history).unwrap());
+        let parsed: Vec<SessionRecord> = jsonl.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+        assert_eq!(parsed, records);
+
+        let json = export(ExportFormat::Json, &records);
+        assert_eq!(serde_json::from_str::<Vec<SessionRecord>>(&json).unwrap(), records);
+        assert_eq!(ExportFormat::from_path(Path::new("week.json")), Some(ExportFormat::Json));
+        assert_eq!(ExportFormat::from_path(Path::new("week.JSONL")), Some(ExportFormat::Jsonl));
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn calendar_has_an_event_per_work_session() {
+        let records = [
+            record(1, SessionType::Work, Some("Plan; review")),
+            record(1, SessionType::ShortBreak, None),
+            record(2, SessionType::Work, None),
+        ];
+        let ics = export(ExportFormat::Ics, &records);
+        assert!(ics.starts_with("BEGIN:VCALENDAR\r\n"));
+        assert!(ics.ends_with("END:VCALENDAR\r\n"));
+        assert_eq!(ics.matches("BEGIN:VEVENT").count(), 2);
+        assert!(ics.contains("SUMMARY:Plan\\; review\r\n"));
+        assert!(ics.contains("SUMMARY:Pomodoro\r\n"));
+        assert!(ics.split("\r\n").all(|line| line.len() <= 75));
+    }
+}
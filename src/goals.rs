@@ -0,0 +1,304 @@
+// Daily and weekly pomodoro targets. The timer keeps a tracker so every front
+// end, the tray and the status bar see the same progress; the goal in effect
+// each day is logged (e.g. ~/.local/share/rust-pomodoro/goals.json on Linux)
+// so streaks stay right after the goal is changed.
+
+use chrono::{Datelike, Duration, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+use crate::config::Config;
+use crate::history::{SessionHistory, SessionOutcome, SessionRecord};
+use crate::notifications;
+use crate::paths;
+use crate::timer::{PomodoroTimer, SessionType};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GoalKind {
+    Daily,
+    Weekly,
+}
+
+/// Pomodoros done towards each goal; a goal of 0 is not set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GoalProgress {
+    pub today: u32,
+    pub daily_goal: u32,
+    pub this_week: u32,
+    pub weekly_goal: u32,
+}
+
+impl GoalProgress {
+    /// e.g. "3/10 today, 12/40 this week", leaving out goals that aren't set.
+    pub fn summary(&self) -> String {
+        let mut parts = Vec::new();
+        if self.daily_goal > 0 {
+            parts.push(format!("{}/{} today", self.today, self.daily_goal));
+        }
+        if self.weekly_goal > 0 {
+            parts.push(format!("{}/{} this week", self.this_week, self.weekly_goal));
+        }
+        parts.join(", ")
+    }
+}
+
+fn counts(record: &SessionRecord) -> bool {
+    record.session_type == SessionType::Work && record.outcome == SessionOutcome::Completed
+}
+
+fn week_start(date: NaiveDate) -> NaiveDate {
+    date - Duration::days(date.weekday().num_days_from_monday() as i64)
+}
+
+/// Counts completed pomodoros per day against the configured goals.
+#[derive(Debug, Clone, Default)]
+pub struct GoalTracker {
+    daily_goal: u32,
+    weekly_goal: u32,
+    per_day: BTreeMap<NaiveDate, u32>,
+}
+
+impl GoalTracker {
+    pub fn new(daily_goal: u32, weekly_goal: u32, records: &[SessionRecord]) -> Self {
+        let mut tracker = Self {
+            daily_goal,
+            weekly_goal,
+            per_day: BTreeMap::new(),
+        };
+        for record in records.iter().filter(|record| counts(record)) {
+            *tracker.per_day.entry(record.started_at.date_naive()).or_default() += 1;
+        }
+        tracker
+    }
+
+    /// Whether any goal is set.
+    pub fn is_set(&self) -> bool {
+        self.daily_goal > 0 || self.weekly_goal > 0
+    }
+
+    // All of the week that `date` falls in
+    fn week_total(&self, date: NaiveDate) -> u32 {
+        let start = week_start(date);
+        self.per_day.range(start..start + Duration::days(7)).map(|(_, count)| count).sum()
+    }
+
+    /// Counts a finished session and returns the goals it just reached.
+    pub fn record(&mut self, record: &SessionRecord) -> Vec<GoalKind> {
+        if !counts(record) {
+            return Vec::new();
+        }
+        let date = record.started_at.date_naive();
+        let day_before = self.per_day.get(&date).copied().unwrap_or(0);
+        let week_before = self.week_total(date);
+        *self.per_day.entry(date).or_default() += 1;
+
+        let mut reached = Vec::new();
+        if self.daily_goal > 0 && day_before + 1 == self.daily_goal {
+            reached.push(GoalKind::Daily);
+        }
+        if self.weekly_goal > 0 && week_before + 1 == self.weekly_goal {
+            reached.push(GoalKind::Weekly);
+        }
+        reached
+    }
+
+    /// Counts `records` afresh, e.g. after sessions were imported, and
+    /// returns the goals for `today` and its week that the change reached.
+    pub fn recount(&mut self, records: &[SessionRecord], today: NaiveDate) -> Vec<GoalKind> {
+        let before = self.progress(today);
+        *self = Self::new(self.daily_goal, self.weekly_goal, records);
+        let after = self.progress(today);
+
+        let mut reached = Vec::new();
+        if self.daily_goal > 0 && before.today < self.daily_goal && after.today >= self.daily_goal {
+            reached.push(GoalKind::Daily);
+        }
+        if self.weekly_goal > 0 && before.this_week < self.weekly_goal && after.this_week >= self.weekly_goal {
+            reached.push(GoalKind::Weekly);
+        }
+        reached
+    }
+
+    pub fn progress(&self, today: NaiveDate) -> GoalProgress {
+        GoalProgress {
+            today: self.per_day.get(&today).copied().unwrap_or(0),
+            daily_goal: self.daily_goal,
+            this_week: self.week_total(today),
+            weekly_goal: self.weekly_goal,
+        }
+    }
+}
+
+/// The daily goal in effect from each date on, as it was changed.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct GoalHistory {
+    daily: BTreeMap<NaiveDate, u32>,
+}
+
+impl GoalHistory {
+    pub fn path() -> Option<PathBuf> {
+        paths::data_file("goals.json")
+    }
+
+    pub fn load() -> Self {
+        let Some(path) = Self::path() else {
+            return Self::default();
+        };
+
+        match fs::read_to_string(&path) {
+            Ok(text) => serde_json::from_str(&text).unwrap_or_else(|e| {
+                eprintln!("Ignoring invalid goal file {}: {}", path.display(), e);
+                Self::default()
+            }),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Self::default(),
+            Err(e) => {
+                eprintln!("Failed to read goal file {}: {}", path.display(), e);
+                Self::default()
+            }
+        }
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let path = Self::path()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no data directory"))?;
+        let json = serde_json::to_string_pretty(self)?;
+        paths::write_atomic(&path, json.as_bytes())
+    }
+
+    /// Notes the goal for `date`; returns whether that changed anything.
+    pub fn set(&mut self, date: NaiveDate, daily_goal: u32) -> bool {
+        if self.goal_on(date) == daily_goal {
+            return false;
+        }
+        self.daily.insert(date, daily_goal);
+        true
+    }
+
+    /// The goal on `date`: the last one set on or before it.
+    pub fn goal_on(&self, date: NaiveDate) -> u32 {
+        self.daily.range(..=date).next_back().map_or(0, |(_, &goal)| goal)
+    }
+
+    /// Consecutive days, up to today, on which the daily goal was met. Today
+    /// only breaks the streak once it is over.
+    pub fn streak(&self, tracker: &GoalTracker, today: NaiveDate) -> u32 {
+        let met = |date: NaiveDate| {
+            let goal = self.goal_on(date);
+            goal > 0 && tracker.per_day.get(&date).copied().unwrap_or(0) >= goal
+        };
+
+        let mut streak = u32::from(met(today));
+        let mut date = today - Duration::days(1);
+        while met(date) {
+            streak += 1;
+            date -= Duration::days(1);
+        }
+        streak
+    }
+}
+
+/// Goals the timer reached since the last call, as messages to show, each
+/// also sent as a notification when `notify` is set.
+pub fn announce_reached(timer: &mut PomodoroTimer, notify: bool) -> Vec<String> {
+    let reached = timer.take_reached_goals();
+    let Some(progress) = timer.status().goal else {
+        return Vec::new();
+    };
+    reached
+        .into_iter()
+        .map(|kind| {
+            let message = match kind {
+                GoalKind::Daily => format!("Daily goal reached: {} pomodoros today!", progress.today),
+                GoalKind::Weekly => format!("Weekly goal reached: {} pomodoros this week!", progress.this_week),
+            };
+            if notify {
+                notifications::goal_reached(&message);
+            }
+            message
+        })
+        .collect()
+}
+
+/// A tracker for the configured goals, seeded from the session history, with
+/// today's goal noted in the goal history.
+pub fn tracker(config: &Config, history: Option<&SessionHistory>, today: NaiveDate) -> GoalTracker {
+    let mut goal_history = GoalHistory::load();
+    if goal_history.set(today, config.daily_goal) {
+        if let Err(e) = goal_history.save() {
+            eprintln!("Failed to save goal history: {}", e);
+        }
+    }
+
+    let records = history
+        .map(|history| history.records())
+        .transpose()
+        .unwrap_or_else(|e| {
+            eprintln!("Failed to read session history: {}", e);
+            None
+        })
+        .unwrap_or_default();
+    GoalTracker::new(config.daily_goal, config.weekly_goal, &records)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Local, TimeZone};
+
+    fn pomodoro(day: u32) -> SessionRecord {
+        SessionRecord::pomodoro(Local.with_ymd_and_hms(2024, 5, day, 10, 0, 0).unwrap())
+    }
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 5, day).unwrap()
+    }
+
+    #[test]
+    fn reports_progress_and_goals_reached() {
+        // 2024-05-13 is a Monday
+        let mut tracker = GoalTracker::new(2, 4, &[pomodoro(12), pomodoro(13), pomodoro(14)]);
+        assert_eq!(tracker.progress(date(14)).summary(), "1/2 today, 2/4 this week");
+
+        assert_eq!(tracker.record(&pomodoro(14)), [GoalKind::Daily]);
+        assert_eq!(tracker.record(&pomodoro(15)), [GoalKind::Weekly]);
+        // Past the goal nothing more is announced
+        assert!(tracker.record(&pomodoro(14)).is_empty());
+
+        let mut skipped = pomodoro(15);
+        skipped.outcome = SessionOutcome::Skipped;
+        assert!(tracker.record(&skipped).is_empty());
+        assert_eq!(tracker.progress(date(15)).this_week, 5);
+    }
+
+    #[test]
+    fn recount_reports_goals_reached_by_added_sessions() {
+        let mut records = vec![pomodoro(13), pomodoro(14)];
+        let mut tracker = GoalTracker::new(2, 4, &records);
+
+        // Sessions from an earlier week don't reach this week's goals
+        records.extend([pomodoro(1), pomodoro(2), pomodoro(3)]);
+        assert!(tracker.recount(&records, date(14)).is_empty());
+
+        records.extend([pomodoro(14), pomodoro(15)]);
+        assert_eq!(tracker.recount(&records, date(14)), [GoalKind::Daily, GoalKind::Weekly]);
+        assert_eq!(tracker.progress(date(14)).summary(), "2/2 today, 4/4 this week");
+        assert!(tracker.recount(&records, date(14)).is_empty());
+    }
+
+    #[test]
+    fn streak_uses_the_goal_in_effect_each_day() {
+        let tracker = GoalTracker::new(0, 0, &[pomodoro(12), pomodoro(13), pomodoro(13), pomodoro(14)]);
+        let mut history = GoalHistory::default();
+        history.set(date(1), 1);
+        assert!(!history.set(date(5), 1));
+        history.set(date(14), 2);
+
+        // 14th misses the new goal of 2; today (15th) isn't over yet
+        assert_eq!(history.streak(&tracker, date(15)), 0);
+        assert_eq!(history.streak(&tracker, date(13)), 2);
+        assert_eq!(history.goal_on(date(20)), 2);
+    }
+}
@@ -0,0 +1,149 @@
+// Terminal-only mode: drives the timer without opening a window, printing the
+// countdown and session changes. Usable over SSH and without a display.
+
+use chrono::Local;
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use crate::cli;
+use crate::config::Config;
+use crate::goals;
+#[cfg(unix)]
+use crate::control;
+#[cfg(target_os = "linux")]
+use crate::dbus;
+use crate::history::SessionHistory;
+use crate::notifications;
+use crate::resume::SavedSession;
+use crate::sound::SoundPlayer;
+use crate::tasks::TaskList;
+use crate::timer::PomodoroTimer;
+
+const TICK: Duration = Duration::from_millis(200);
+
+pub fn run(config: Config) {
+    cli::attach_parent_console();
+
+    let running = Arc::new(AtomicBool::new(true));
+    {
+        let running = running.clone();
+        if let Err(e) = ctrlc::set_handler(move || running.store(false, Ordering::SeqCst)) {
+            eprintln!("Failed to install Ctrl-C handler: {}", e);
+        }
+    }
+
+    let mut tasks = TaskList::load();
+    let history = SessionHistory::open_default();
+    let mut sound = SoundPlayer::new();
+    let mut timer = PomodoroTimer::new();
+    timer.set_sequence(config.sequence());
+    timer.set_auto_start(config.auto_start_breaks, config.auto_start_work);
+    timer.set_flow_mode(config.flow_mode, config.overtime_to_break);
+    timer.set_goals(goals::tracker(&config, history.as_ref(), Local::now().date_naive()));
+    timer.set_task(tasks.active().map(|task| task.tag()));
+    // With no one to ask, carry on from where the timer last closed rather
+    // than overwrite that state at exit
+    let resumed = SavedSession::load();
+    if let Some(saved) = &resumed {
+        timer.restore(&saved.timer, saved.time_away());
+    }
+    timer.start();
+    let shared = Arc::new(Mutex::new(timer));
+
+    #[cfg(unix)]
+    let _control = control::ControlServer::start(shared.clone(), |_| {})
+        .map_err(|e| eprintln!("Control socket unavailable: {}", e))
+        .ok();
+    #[cfg(target_os = "linux")]
+    let _dbus = dbus::DbusService::start(shared.clone(), || {})
+        .map_err(|e| eprintln!("D-Bus service unavailable: {}", e))
+        .ok();
+
+    println!("Pomodoro timer started. Press Ctrl-C to stop.");
+    if let Some(saved) = &resumed {
+        println!(
+            "Resumed the {} saved at {}.",
+            saved.timer.session_type.name().to_lowercase(),
+            saved.saved_at.format("%H:%M")
+        );
+    }
+    if let Some(task) = tasks.active() {
+        println!("Working on: {}", task.name);
+    }
+
+    let mut stdout = io::stdout();
+    while running.load(Ordering::SeqCst) {
+        let mut timer = shared.lock().unwrap();
+        let time = timer.get_time_string();
+
+        if let Some(finished) = timer.just_completed() {
+            timer.clear_completed_flag();
+            // Clear the countdown line before printing the transition
+            print!("\r{:60}\r", "");
+            if timer.is_in_overtime() {
+                println!(
+                    "[{}] {} time is up, counting overtime until skipped",
+                    Local::now().format("%H:%M"),
+                    finished.name(),
+                );
+            } else {
+                let next = if timer.is_waiting() { "waiting to start" } else { "started" };
+                println!(
+                    "[{}] {} finished, {} {}",
+                    Local::now().format("%H:%M"),
+                    finished.name(),
+                    timer.get_session_type().name(),
+                    next,
+                );
+            }
+            if config.notifications_enabled {
+                notifications::session_finished(finished, &shared, || {});
+            }
+            sound.session_finished(finished, &config);
+        }
+        sound.update_ticking(timer.get_session_type(), timer.is_running(), &config);
+        record_finished_sessions(&mut timer, &mut tasks, history.as_ref());
+        for message in goals::announce_reached(&mut timer, config.notifications_enabled) {
+            print!("\r{:60}\r", "");
+            println!("[{}] {}", Local::now().format("%H:%M"), message);
+        }
+
+        print!(
+            "\r{}  {}  (session {} of {})  ",
+            timer.get_session_type().name(),
+            time,
+            timer.get_session_count(),
+            timer.get_cycle_length(),
+        );
+        let _ = stdout.flush();
+        drop(timer);
+
+        thread::sleep(TICK);
+    }
+
+    println!();
+    let mut timer = shared.lock().unwrap();
+    record_finished_sessions(&mut timer, &mut tasks, history.as_ref());
+    if let Err(e) = SavedSession::store(&mut timer) {
+        eprintln!("Failed to save session state: {}", e);
+    }
+    println!("Stopped.");
+}
+
+/// Appends the timer's finished sessions to the history and credits their tasks.
+pub fn record_finished_sessions(timer: &mut PomodoroTimer, tasks: &mut TaskList, history: Option<&SessionHistory>) {
+    let records = timer.take_finished_sessions();
+    if tasks.record_sessions(&records) {
+        if let Err(e) = tasks.save() {
+            eprintln!("Failed to save tasks: {}", e);
+        }
+    }
+    if let Some(history) = history {
+        if let Err(e) = history.append_all(&records) {
+            eprintln!("Failed to write session history: {}", e);
+        }
+    }
+}
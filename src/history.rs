@@ -0,0 +1,200 @@
+// Append-only log of finished sessions, stored as one JSON record per line
+// (e.g. ~/.local/share/rust-pomodoro/history.jsonl on Linux).
+
+use chrono::{DateTime, Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::PathBuf;
+
+use crate::paths;
+use crate::timer::SessionType;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SessionOutcome {
+    /// The countdown reached zero.
+    Completed,
+    /// The user skipped ahead to the next session.
+    Skipped,
+    /// The user reset the session after it had started.
+    Reset,
+    /// The user abandoned the work session; it does not count as a pomodoro.
+    Voided,
+}
+
+/// Where an interruption came from, in Pomodoro technique terms.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InterruptionKind {
+    /// The urge to do something else: check mail, fetch coffee, ...
+    Internal,
+    /// Someone or something else: a call, a colleague, ...
+    External,
+}
+
+/// An interruption logged during a work session.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Interruption {
+    pub kind: InterruptionKind,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    pub at: DateTime<Local>,
+    /// Whether the timer was paused for it; this is the pause's reason.
+    #[serde(default)]
+    pub paused: bool,
+}
+
+/// The task a work session was spent on.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionTask {
+    pub id: u64,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionRecord {
+    pub session_type: SessionType,
+    pub outcome: SessionOutcome,
+    pub planned_secs: u64,
+    /// Includes any overtime.
+    pub focused_secs: u64,
+    /// Time a flow-mode work session ran on past its planned end.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub overtime_secs: u64,
+    /// Net time added (positive) or taken off (negative) with extend and
+    /// shorten; already part of `planned_secs`.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub adjusted_secs: i64,
+    pub started_at: DateTime<Local>,
+    pub ended_at: DateTime<Local>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub task: Option<SessionTask>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub interruptions: Vec<Interruption>,
+    /// Why a voided session was abandoned.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub void_reason: Option<String>,
+}
+
+fn is_zero<T: Default + PartialEq>(value: &T) -> bool {
+    *value == T::default()
+}
+
+#[cfg(test)]
+impl SessionRecord {
+    /// A 25-minute work session completed from `started_at`, for tests to
+    /// adjust with struct update syntax.
+    pub fn pomodoro(started_at: DateTime<Local>) -> Self {
+        Self {
+            session_type: SessionType::Work,
+            outcome: SessionOutcome::Completed,
+            planned_secs: 25 * 60,
+            focused_secs: 25 * 60,
+            overtime_secs: 0,
+            adjusted_secs: 0,
+            started_at,
+            ended_at: started_at + chrono::Duration::minutes(25),
+            task: None,
+            interruptions: Vec::new(),
+            void_reason: None,
+        }
+    }
+}
+
+pub struct SessionHistory {
+    path: PathBuf,
+}
+
+impl SessionHistory {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    /// The history file in the per-user data directory.
+    pub fn open_default() -> Option<Self> {
+        paths::data_file("history.jsonl").map(Self::new)
+    }
+
+    pub fn append(&self, record: &SessionRecord) -> io::Result<()> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        let mut line = serde_json::to_string(record)?;
+        line.push('\n');
+        let mut file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+        file.write_all(line.as_bytes())
+    }
+
+    pub fn append_all(&self, records: &[SessionRecord]) -> io::Result<()> {
+        for record in records {
+            self.append(record)?;
+        }
+        Ok(())
+    }
+
+    /// All records in the order they were written. Lines that fail to parse
+    /// (e.g. a partial write from a crash) are skipped.
+    pub fn records(&self) -> io::Result<Vec<SessionRecord>> {
+        let text = match fs::read_to_string(&self.path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+
+        let mut records = Vec::new();
+        for (number, line) in text.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str(line) {
+                Ok(record) => records.push(record),
+                Err(e) => eprintln!("Skipping history line {}: {}", number + 1, e),
+            }
+        }
+        Ok(records)
+    }
+
+    /// Records whose session started on any local date from `first` to `last`, inclusive.
+    pub fn on_dates(&self, first: NaiveDate, last: NaiveDate) -> io::Result<Vec<SessionRecord>> {
+        let mut records = self.records()?;
+        records.retain(|record| {
+            let date = record.started_at.date_naive();
+            date >= first && date <= last
+        });
+        Ok(records)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn record_at(day: u32, hour: u32) -> SessionRecord {
+        SessionRecord::pomodoro(Local.with_ymd_and_hms(2024, 3, day, hour, 0, 0).unwrap())
+    }
+
+    #[test]
+    fn appends_and_queries_by_date_range() {
+        let path = std::env::temp_dir().join(format!("pomodoro-history-{}.jsonl", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let history = SessionHistory::new(path.clone());
+
+        for record in [record_at(1, 9), record_at(2, 9), record_at(2, 23), record_at(4, 9)] {
+            history.append(&record).unwrap();
+        }
+        // A torn final write must not hide the earlier records
+        OpenOptions::new().append(true).open(&path).unwrap().write_all(b"{\"session_type\":").unwrap();
+
+        assert_eq!(history.records().unwrap().len(), 4);
+
+        let day = NaiveDate::from_ymd_opt(2024, 3, 2).unwrap();
+        assert_eq!(history.on_dates(day, day).unwrap(), vec![record_at(2, 9), record_at(2, 23)]);
+        let last = NaiveDate::from_ymd_opt(2024, 3, 4).unwrap();
+        assert_eq!(history.on_dates(day, last).unwrap().len(), 3);
+
+        fs::remove_file(path).unwrap();
+    }
+}
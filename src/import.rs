@@ -0,0 +1,479 @@
+// Session history import from other Pomodoro apps: CSV with a configurable
+// column mapping, and JSON (an array of objects or one object per line). The
+// defaults match this app's own CSV and JSON exports.
+
+use chrono::{DateTime, Duration, Local, NaiveDateTime, TimeZone};
+use clap::{Args, ValueEnum};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use crate::history::{SessionOutcome, SessionRecord, SessionTask};
+use crate::tasks::TaskList;
+use crate::timer::SessionType;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ImportFormat {
+    Csv,
+    /// A JSON array, or JSON Lines
+    Json,
+}
+
+impl ImportFormat {
+    /// The format a file name suggests, if its extension is known.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()?.to_ascii_lowercase().as_str() {
+            "csv" => Some(ImportFormat::Csv),
+            "json" | "jsonl" => Some(ImportFormat::Json),
+            _ => None,
+        }
+    }
+}
+
+/// Which column (or JSON key) holds each field; names are matched
+/// case-insensitively.
+#[derive(Args, Debug, Clone, PartialEq)]
+pub struct ColumnMap {
+    /// Column with the session's start time
+    #[arg(long = "start-column", value_name = "NAME", default_value = "started_at")]
+    pub start: String,
+
+    /// Column with the session's end time
+    #[arg(long = "end-column", value_name = "NAME", default_value = "ended_at")]
+    pub end: String,
+
+    /// Column with the session's length, used when there is no end time
+    /// (minutes, or h:mm:ss / mm:ss)
+    #[arg(long = "duration-column", value_name = "NAME", default_value = "duration")]
+    pub duration: String,
+
+    /// Column with the session type (work, short break, long break); work if absent
+    #[arg(long = "type-column", value_name = "NAME", default_value = "session_type")]
+    pub session_type: String,
+
+    /// Column with the task name
+    #[arg(long = "task-column", value_name = "NAME", default_value = "task")]
+    pub task: String,
+
+    /// Column with the outcome (completed, skipped, reset, voided); completed if absent
+    #[arg(long = "outcome-column", value_name = "NAME", default_value = "outcome")]
+    pub outcome: String,
+}
+
+impl Default for ColumnMap {
+    fn default() -> Self {
+        Self {
+            start: "started_at".to_string(),
+            end: "ended_at".to_string(),
+            duration: "duration".to_string(),
+            session_type: "session_type".to_string(),
+            task: "task".to_string(),
+            outcome: "outcome".to_string(),
+        }
+    }
+}
+
+/// What an import found: the new records, how many were already known, and
+/// why the rest couldn't be read.
+#[derive(Debug, Default)]
+pub struct ImportReport {
+    pub records: Vec<SessionRecord>,
+    pub duplicates: usize,
+    pub errors: Vec<String>,
+}
+
+// Sessions are the same if they started in the same second
+fn key(record: &SessionRecord) -> i64 {
+    record.started_at.timestamp()
+}
+
+/// Parses `text` and keeps the sessions that aren't in `existing` yet. Task
+/// names are linked to the task list's tasks where one has that name.
+pub fn parse(
+    format: ImportFormat,
+    text: &str,
+    columns: &ColumnMap,
+    existing: &[SessionRecord],
+    tasks: &TaskList,
+) -> ImportReport {
+    let rows = match format {
+        ImportFormat::Csv => csv_rows(text),
+        ImportFormat::Json => json_rows(text),
+    };
+    let mut report = ImportReport::default();
+    let rows = match rows {
+        Ok(rows) => rows,
+        Err(e) => {
+            report.errors.push(e);
+            return report;
+        }
+    };
+
+    let mut seen: HashSet<i64> = existing.iter().map(key).collect();
+    for (location, row) in rows {
+        let record = match row {
+            Row::Native(record) => Ok(*record),
+            Row::Fields(fields) => convert(&fields, columns, tasks),
+            Row::Invalid(e) => Err(e),
+        };
+        match record {
+            Ok(record) if !seen.insert(key(&record)) => report.duplicates += 1,
+            Ok(record) => report.records.push(record),
+            Err(e) => report.errors.push(format!("{}: {}", location, e)),
+        }
+    }
+    report.records.sort_by_key(|record| record.started_at);
+    report
+}
+
+enum Row {
+    // A record in this app's own format, taken over as is
+    Native(Box<SessionRecord>),
+    // Field values by lower-cased column name
+    Fields(HashMap<String, String>),
+    // Not a record at all
+    Invalid(String),
+}
+
+// Splits CSV text into records of fields, with the line each record starts
+// on. Quoted fields may contain commas, doubled quotes and line breaks.
+fn split_csv(text: &str) -> Result<Vec<(usize, Vec<String>)>, String> {
+    let mut records = Vec::new();
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut line = 1;
+    let mut start_line = 1;
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            '"' if quoted => quoted = false,
+            '"' if field.is_empty() => quoted = true,
+            ',' if !quoted => fields.push(std::mem::take(&mut field)),
+            '\r' if !quoted && chars.peek() == Some(&'\n') => {}
+            '\n' if !quoted => {
+                fields.push(std::mem::take(&mut field));
+                if fields.iter().any(|field| !field.is_empty()) {
+                    records.push((start_line, std::mem::take(&mut fields)));
+                }
+                fields.clear();
+                line += 1;
+                start_line = line;
+            }
+            _ => {
+                if c == '\n' {
+                    line += 1;
+                }
+                field.push(c);
+            }
+        }
+    }
+    if quoted {
+        return Err(format!("line {}: unterminated quoted field", start_line));
+    }
+    fields.push(field);
+    if fields.iter().any(|field| !field.is_empty()) {
+        records.push((start_line, fields));
+    }
+    Ok(records)
+}
+
+fn csv_rows(text: &str) -> Result<Vec<(String, Row)>, String> {
+    let mut records = split_csv(text.trim_start_matches('\u{feff}'))?.into_iter();
+    let Some((_, header)) = records.next() else {
+        return Ok(Vec::new());
+    };
+    let header: Vec<String> = header.iter().map(|name| name.trim().to_lowercase()).collect();
+
+    Ok(records
+        .map(|(line, values)| {
+            let fields = header.iter().cloned().zip(values).collect();
+            (format!("line {}", line), Row::Fields(fields))
+        })
+        .collect())
+}
+
+fn json_rows(text: &str) -> Result<Vec<(String, Row)>, String> {
+    let values: Vec<(String, Result<Value, String>)> = if text.trim_start().starts_with('[') {
+        let Value::Array(values) = serde_json::from_str(text).map_err(|e| format!("invalid JSON: {}", e))? else {
+            unreachable!("text starts with '['");
+        };
+        values
+            .into_iter()
+            .enumerate()
+            .map(|(i, value)| (format!("record {}", i + 1), Ok(value)))
+            .collect()
+    } else {
+        text.lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(number, line)| {
+                let value = serde_json::from_str(line).map_err(|e| format!("invalid JSON: {}", e));
+                (format!("line {}", number + 1), value)
+            })
+            .collect()
+    };
+
+    Ok(values
+        .into_iter()
+        .map(|(location, value)| {
+            let value = match value {
+                Ok(value) => value,
+                Err(e) => return (location, Row::Invalid(e)),
+            };
+            if let Ok(record) = serde_json::from_value::<SessionRecord>(value.clone()) {
+                return (location, Row::Native(Box::new(record)));
+            }
+            let row = match value {
+                Value::Object(object) => Row::Fields(
+                    object
+                        .into_iter()
+                        .filter_map(|(name, value)| Some((name.to_lowercase(), scalar(value)?)))
+                        .collect(),
+                ),
+                _ => Row::Invalid("not a JSON object".to_string()),
+            };
+            (location, row)
+        })
+        .collect())
+}
+
+// A JSON value as text; a nested object stands for its "name" (e.g. a task)
+fn scalar(value: Value) -> Option<String> {
+    match value {
+        Value::String(text) => Some(text),
+        Value::Number(number) => Some(number.to_string()),
+        Value::Bool(flag) => Some(flag.to_string()),
+        Value::Object(mut object) => match object.remove("name") {
+            Some(Value::String(name)) => Some(name),
+            _ => None,
+        },
+        Value::Null | Value::Array(_) => None,
+    }
+}
+
+fn parse_time(text: &str) -> Option<DateTime<Local>> {
+    if let Ok(time) = DateTime::parse_from_rfc3339(text) {
+        return Some(time.with_timezone(&Local));
+    }
+    // Unix time, in seconds or milliseconds
+    if let Ok(number) = text.parse::<i64>() {
+        let time = if number > 100_000_000_000 {
+            Local.timestamp_millis_opt(number)
+        } else {
+            Local.timestamp_opt(number, 0)
+        };
+        return time.single();
+    }
+    ["%Y-%m-%d %H:%M:%S", "%Y-%m-%dT%H:%M:%S", "%Y-%m-%d %H:%M", "%Y/%m/%d %H:%M:%S", "%Y/%m/%d %H:%M"]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(text, format).ok())
+        .and_then(|time| Local.from_local_datetime(&time).earliest())
+}
+
+// Minutes as a number, or a clock-style h:mm:ss / mm:ss; None if it is
+// negative or too long to represent
+fn parse_duration(text: &str) -> Option<Duration> {
+    if let Ok(minutes) = text.parse::<f64>() {
+        let secs = (minutes * 60.0).round();
+        // Also rules out NaN and infinity; the cast saturates otherwise
+        if !(0.0..i64::MAX as f64).contains(&secs) {
+            return None;
+        }
+        return Duration::try_seconds(secs as i64);
+    }
+    let parts: Vec<i64> = text.split(':').map(|part| part.parse().ok()).collect::<Option<_>>()?;
+    let secs = match parts[..] {
+        [minutes, secs] => minutes.checked_mul(60)?.checked_add(secs)?,
+        [hours, minutes, secs] => hours
+            .checked_mul(3600)?
+            .checked_add(minutes.checked_mul(60)?)?
+            .checked_add(secs)?,
+        _ => return None,
+    };
+    if secs < 0 {
+        return None;
+    }
+    Duration::try_seconds(secs)
+}
+
+fn parse_session_type(text: &str) -> Option<SessionType> {
+    let normalized: String = text
+        .chars()
+        .filter(|c| !matches!(c, ' ' | '_' | '-'))
+        .collect::<String>()
+        .to_lowercase();
+    match normalized.as_str() {
+        "work" | "pomodoro" | "focus" => Some(SessionType::Work),
+        "shortbreak" | "break" => Some(SessionType::ShortBreak),
+        "longbreak" => Some(SessionType::LongBreak),
+        _ => None,
+    }
+}
+
+fn parse_outcome(text: &str) -> Option<SessionOutcome> {
+    match text.to_lowercase().as_str() {
+        "completed" | "complete" | "done" | "finished" => Some(SessionOutcome::Completed),
+        "skipped" => Some(SessionOutcome::Skipped),
+        "reset" => Some(SessionOutcome::Reset),
+        "voided" | "void" | "abandoned" => Some(SessionOutcome::Voided),
+        _ => None,
+    }
+}
+
+fn convert(fields: &HashMap<String, String>, columns: &ColumnMap, tasks: &TaskList) -> Result<SessionRecord, String> {
+    let field = |name: &str| {
+        fields
+            .get(&name.to_lowercase())
+            .map(|value| value.trim())
+            .filter(|value| !value.is_empty())
+    };
+
+    let start = field(&columns.start).ok_or_else(|| format!("no start time in column '{}'", columns.start))?;
+    let started_at = parse_time(start).ok_or_else(|| format!("cannot read start time '{}'", start))?;
+    let ended_at = match (field(&columns.end), field(&columns.duration)) {
+        (Some(end), _) => parse_time(end).ok_or_else(|| format!("cannot read end time '{}'", end))?,
+        (None, Some(duration)) => parse_duration(duration)
+            .and_then(|duration| started_at.checked_add_signed(duration))
+            .ok_or_else(|| format!("cannot read duration '{}'", duration))?,
+        (None, None) => {
+            return Err(format!(
+                "no end time or duration in columns '{}' / '{}'",
+                columns.end, columns.duration
+            ))
+        }
+    };
+    if ended_at < started_at {
+        return Err("ends before it starts".to_string());
+    }
+
+    let session_type = match field(&columns.session_type) {
+        Some(text) => parse_session_type(text).ok_or_else(|| format!("unknown session type '{}'", text))?,
+        None => SessionType::Work,
+    };
+    let outcome = match field(&columns.outcome) {
+        Some(text) => parse_outcome(text).ok_or_else(|| format!("unknown outcome '{}'", text))?,
+        None => SessionOutcome::Completed,
+    };
+    // Tasks that aren't in the task list get id 0
+    let task = field(&columns.task).map(|name| {
+        let id = tasks
+            .tasks()
+            .iter()
+            .find(|task| task.name.eq_ignore_ascii_case(name))
+            .map_or(0, |task| task.id);
+        SessionTask { id, name: name.to_string() }
+    });
+
+    let secs = (ended_at - started_at).num_seconds() as u64;
+    Ok(SessionRecord {
+        session_type,
+        outcome,
+        planned_secs: secs,
+        focused_secs: secs,
+        overtime_secs: 0,
+        adjusted_secs: 0,
+        started_at,
+        ended_at,
+        task,
+        interruptions: Vec::new(),
+        void_reason: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::{self, ExportFormat};
+
+    fn columns() -> ColumnMap {
+        ColumnMap {
+            start: "Start".to_string(),
+            duration: "Minutes".to_string(),
+            session_type: "Kind".to_string(),
+            task: "Project".to_string(),
+            ..ColumnMap::default()
+        }
+    }
+
+    #[test]
+    fn maps_csv_columns_and_reports_bad_rows() {
+        let text = "Start,Minutes,Kind,Project\n\
+                    2024-05-01 09:00,25,Pomodoro,\"Report, draft\"\n\
+                    2024-05-01 09:25,5,Short Break,\n\
+                    yesterday,25,Pomodoro,\n\
+                    2024-05-01 09:30:00,25:00,nap,\n\
+                    2024-05-01 09:00,25,Pomodoro,Again\n";
+        let report = parse(ImportFormat::Csv, text, &columns(), &[], &TaskList::default());
+
+        assert_eq!(report.records.len(), 2);
+        assert_eq!(report.duplicates, 1);
+        assert_eq!(report.errors, ["line 4: cannot read start time 'yesterday'", "line 5: unknown session type 'nap'"]);
+
+        let work = &report.records[0];
+        assert_eq!(work.session_type, SessionType::Work);
+        assert_eq!(work.focused_secs, 25 * 60);
+        assert_eq!(work.task.as_ref().unwrap().name, "Report, draft");
+        assert_eq!(report.records[1].session_type, SessionType::ShortBreak);
+        assert!(report.records[1].task.is_none());
+    }
+
+    #[test]
+    fn reads_json_and_skips_sessions_already_in_history() {
+        let text = r#"[
+            {"start": 1714554000, "end": "2024-05-01T09:25:00Z", "task": {"name": "Email"}},
+            {"start": "2024-05-01T10:00:00+00:00", "duration": "0:25:00", "outcome": "skipped"},
+            "oops"
+        ]"#;
+        let columns = ColumnMap {
+            start: "start".to_string(),
+            end: "end".to_string(),
+            ..ColumnMap::default()
+        };
+        let first = parse(ImportFormat::Json, text, &columns, &[], &TaskList::default());
+        assert_eq!(first.records.len(), 2);
+        assert_eq!(first.records[1].outcome, SessionOutcome::Skipped);
+        assert_eq!(first.errors, ["record 3: not a JSON object"]);
+
+        let again = parse(ImportFormat::Json, text, &columns, &first.records, &TaskList::default());
+        assert!(again.records.is_empty());
+        assert_eq!(again.duplicates, 2);
+
+        // The app's own exports come back in unchanged
+        let mut jsonl = Vec::new();
+        export::write(ExportFormat::Jsonl, &first.records, &mut jsonl).unwrap();
+        let jsonl = String::from_utf8(jsonl).unwrap();
+        let round_trip = parse(ImportFormat::Json, &jsonl, &ColumnMap::default(), &[], &TaskList::default());
+        assert_eq!(round_trip.records, first.records);
+    }
+
+    #[test]
+    fn rejects_durations_out_of_range() {
+        let text = "Start,Minutes\n\
+                    2024-05-01 09:00,1e12\n\
+                    2024-05-01 10:00,inf\n\
+                    2024-05-01 11:00,NaN\n\
+                    2024-05-01 12:00,99999999999999:00:00\n\
+                    2024-05-01 13:00,-5\n\
+                    2024-05-01 14:00,1:30:00\n";
+        let report = parse(ImportFormat::Csv, text, &columns(), &[], &TaskList::default());
+
+        assert_eq!(report.records.len(), 1);
+        assert_eq!(report.records[0].focused_secs, 90 * 60);
+        assert_eq!(
+            report.errors,
+            [
+                "line 2: cannot read duration '1e12'",
+                "line 3: cannot read duration 'inf'",
+                "line 4: cannot read duration 'NaN'",
+                "line 5: cannot read duration '99999999999999:00:00'",
+                "line 6: cannot read duration '-5'",
+            ]
+        );
+    }
+}
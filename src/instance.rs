@@ -0,0 +1,63 @@
+// Keeps a single timer running per user. The first instance holds an OS lock
+// on a file next to the control socket; later launches forward their action
+// to it over the socket and exit. The OS releases the lock when its holder
+// dies, so a crashed instance never blocks the next launch.
+
+use std::fs::{File, OpenOptions, TryLockError};
+use std::io::{self, Write};
+use std::path::Path;
+
+use crate::paths;
+
+pub struct InstanceLock {
+    // The lock lasts as long as the file stays open
+    _file: File,
+}
+
+impl InstanceLock {
+    /// Returns `Ok(None)` if another instance already holds the lock.
+    pub fn acquire() -> io::Result<Option<Self>> {
+        Self::acquire_at(&paths::runtime_file("lock"))
+    }
+
+    fn acquire_at(path: &Path) -> io::Result<Option<Self>> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)?;
+
+        match file.try_lock() {
+            Ok(()) => {}
+            Err(TryLockError::WouldBlock) => return Ok(None),
+            Err(TryLockError::Error(e)) => return Err(e),
+        }
+
+        // Only informational, e.g. for finding the process that holds it
+        file.set_len(0)?;
+        write!(file, "{}", std::process::id())?;
+        Ok(Some(Self { _file: file }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lock_is_released_when_dropped() {
+        let path = std::env::temp_dir().join(format!("rust-pomodoro-test-{}.lock", std::process::id()));
+
+        let first = InstanceLock::acquire_at(&path).unwrap();
+        assert!(first.is_some());
+        assert!(InstanceLock::acquire_at(&path).unwrap().is_none());
+
+        drop(first);
+        assert!(InstanceLock::acquire_at(&path).unwrap().is_some());
+        let _ = std::fs::remove_file(&path);
+    }
+}
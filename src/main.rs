@@ -1,60 +1,605 @@
 use eframe::egui;
-use egui::{Color32, Rect, Vec2, RichText};
+use egui::{Align2, Color32, FontId, Pos2, Rect, Vec2, RichText};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
-use std::time::Duration;
-use notify_rust::Notification;
+use std::time::{Duration, Instant};
+use chrono::{Local, NaiveDate};
 
 mod overlay_window;
 mod timer;
+mod cli;
+mod clock;
+mod config;
+mod control;
+#[cfg(target_os = "linux")]
+mod dbus;
+mod export;
+mod goals;
+mod headless;
+mod history;
+mod import;
+mod instance;
+mod notifications;
+mod paths;
+mod resume;
+mod sequence;
+mod sound;
+mod stats;
+mod statusbar;
+mod tasks;
+mod tui;
 mod check_transparency;
 mod windows_transparency;
 mod transparent_overlay;
 #[cfg(target_os = "windows")]
 mod windows_overlay;
-// mod tray;
+mod tray;
 
 #[cfg(feature = "bevy-overlay")]
 mod bevy_overlay;
 
+use clap::Parser;
+use cli::{Cli, Command};
+use config::Config;
+use export::{ExportFilter, ExportFormat};
+use control::Request;
+use history::{InterruptionKind, SessionHistory};
+use instance::InstanceLock;
+use resume::SavedSession;
+use sequence::SessionSequence;
+use sound::SoundPlayer;
+use stats::Stats;
+use tasks::TaskList;
 use timer::{PomodoroTimer, SessionType};
+use tray::{TrayEvent, TrayManager};
 
 // Overlay imports removed - using transparent_overlay module
 
 const WINDOW_WIDTH: f32 = 400.0;
-const WINDOW_HEIGHT_COLLAPSED: f32 = 450.0;
-const WINDOW_HEIGHT_EXPANDED: f32 = 650.0;
+const WINDOW_HEIGHT_COLLAPSED: f32 = 604.0;
+const SETTINGS_PANEL_HEIGHT: f32 = 542.0;
+const STATS_PANEL_HEIGHT: f32 = 480.0;
+const TASKS_PANEL_HEIGHT: f32 = 320.0;
+const CHART_HEIGHT: f32 = 100.0;
+const SNAPSHOT_INTERVAL: Duration = Duration::from_secs(5);
+// How much the +/- buttons, keys and notification action add or take off
+const ADJUST_STEP: Duration = Duration::from_secs(5 * 60);
 
 pub struct PomodoroApp {
     timer: Arc<Mutex<PomodoroTimer>>,
     show_settings: bool,
-    work_duration: u32,
-    short_break: u32,
-    long_break: u32,
+    show_stats: bool,
+    stats: Stats,
+    show_tasks: bool,
+    tasks: TaskList,
+    new_task_name: String,
+    new_task_estimate: u32,
+    interruption_note: String,
+    pause_on_interruption: bool,
+    void_reason: String,
+    renaming_task: Option<(u64, String)>,
+    config: Config,
+    // The settings as last applied (at first, as this run started), and as
+    // saved; they differ by one-off durations from the command line
+    launch_config: Config,
+    saved_config: Config,
+    use_custom_sequence: bool,
+    custom_sequence: String,
+    sequence_error: Option<String>,
+    pending_resume: Option<SavedSession>,
+    last_snapshot: Instant,
+    history: Option<SessionHistory>,
+    // Days in a row the daily goal was met, for the stats panel
+    goal_streak: u32,
+    // Export bounds as typed: dates as YYYY-MM-DD, each may be left empty
+    export_from: String,
+    export_to: String,
+    export_task: String,
+    export_message: Option<String>,
+    sound: SoundPlayer,
+    // Kept alive for as long as the window is open
+    #[cfg(unix)]
+    _control: Option<control::ControlServer>,
+    #[cfg(target_os = "linux")]
+    _dbus: Option<dbus::DbusService>,
+    tray: Option<TrayManager>,
+    // Set by the tray's Quit item so closing isn't turned into hiding
+    quitting: Arc<AtomicBool>,
+    // Set by the control socket when e.g. an import added to the history
+    history_changed: Arc<AtomicBool>,
 }
 
-impl Default for PomodoroApp {
-    fn default() -> Self {
+impl PomodoroApp {
+    fn new(config: Config) -> Self {
+        let sequence = config.sequence();
+        let tasks = TaskList::load();
+        let mut timer = PomodoroTimer::new();
+        timer.set_sequence(sequence.clone());
+        timer.set_auto_start(config.auto_start_breaks, config.auto_start_work);
+        timer.set_flow_mode(config.flow_mode, config.overtime_to_break);
+        timer.set_task(tasks.active().map(|task| task.tag()));
+        let history = SessionHistory::open_default();
+        timer.set_goals(goals::tracker(&config, history.as_ref(), Local::now().date_naive()));
+
         Self {
-            timer: Arc::new(Mutex::new(PomodoroTimer::new())),
+            timer: Arc::new(Mutex::new(timer)),
             show_settings: false,
-            work_duration: 25,
-            short_break: 5,
-            long_break: 15,
+            show_stats: false,
+            stats: Stats::default(),
+            show_tasks: false,
+            tasks,
+            new_task_name: String::new(),
+            new_task_estimate: 1,
+            renaming_task: None,
+            interruption_note: String::new(),
+            pause_on_interruption: false,
+            void_reason: String::new(),
+            use_custom_sequence: config.custom_sequence.is_some(),
+            custom_sequence: sequence.to_string(),
+            sequence_error: None,
+            pending_resume: SavedSession::load(),
+            last_snapshot: Instant::now(),
+            history,
+            goal_streak: 0,
+            export_from: String::new(),
+            export_to: String::new(),
+            export_task: String::new(),
+            export_message: None,
+            sound: SoundPlayer::new(),
+            launch_config: config.clone(),
+            saved_config: Config::load(),
+            config,
+            #[cfg(unix)]
+            _control: None,
+            #[cfg(target_os = "linux")]
+            _dbus: None,
+            tray: None,
+            quitting: Arc::new(AtomicBool::new(false)),
+            history_changed: Arc::new(AtomicBool::new(false)),
         }
     }
-}
 
-impl PomodoroApp {
-    fn show_tomato_overlay(&mut self) {
+    fn record_finished_sessions(&mut self) {
+        let mut timer = self.timer.lock().unwrap();
+        let records = timer.take_finished_sessions();
+        goals::announce_reached(&mut timer, self.config.notifications_enabled);
+        drop(timer);
+        if self.tasks.record_sessions(&records) {
+            self.save_tasks();
+        }
+
+        let Some(history) = &self.history else {
+            return;
+        };
+        if let Err(e) = history.append_all(&records) {
+            eprintln!("Failed to write session history: {}", e);
+        }
+
+        if self.show_stats && !records.is_empty() {
+            self.refresh_stats();
+        }
+    }
+
+    fn refresh_stats(&mut self) {
+        let Some(history) = &self.history else {
+            return;
+        };
+        let today = Local::now().date_naive();
+        match history.records() {
+            Ok(records) => self.stats = Stats::compute(&records, today),
+            Err(e) => eprintln!("Failed to read session history: {}", e),
+        }
+        self.goal_streak = goals::GoalHistory::load().streak(self.timer.lock().unwrap().goals(), today);
+    }
+
+    fn resize_window(&self, ctx: &egui::Context) {
+        let mut height = WINDOW_HEIGHT_COLLAPSED;
+        if self.show_settings {
+            height += SETTINGS_PANEL_HEIGHT;
+        }
+        if self.show_stats {
+            height += STATS_PANEL_HEIGHT;
+        }
+        if self.show_tasks {
+            height += TASKS_PANEL_HEIGHT;
+        }
+
+        // Request window resize through context
+        ctx.send_viewport_cmd(egui::ViewportCommand::InnerSize(Vec2::new(WINDOW_WIDTH, height)));
+    }
+
+    fn save_tasks(&self) {
+        if let Err(e) = self.tasks.save() {
+            eprintln!("Failed to save tasks: {}", e);
+        }
+    }
+
+    fn set_active_task(&mut self, id: Option<u64>) {
+        self.tasks.set_active(id);
+        self.timer.lock().unwrap().set_task(self.tasks.active().map(|task| task.tag()));
+        self.save_tasks();
+    }
+
+    fn show_task_picker(&mut self, ui: &mut egui::Ui) {
+        // Switching tasks mid-pomodoro would split its attribution, so the
+        // picker is locked while a work session is running
+        let timer = self.timer.lock().unwrap();
+        let locked = timer.is_running() && timer.get_session_type() == SessionType::Work;
+        drop(timer);
+
+        let active = self.tasks.active().map(|task| task.id);
+        let mut selected = active;
+        let selected_text = self
+            .tasks
+            .active()
+            .map_or("No task".to_string(), |task| task.name.clone());
+
+        ui.add_enabled_ui(!locked, |ui| {
+            egui::ComboBox::from_id_source("active_task")
+                .width(WINDOW_WIDTH - 80.0)
+                .selected_text(selected_text)
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut selected, None, "No task");
+                    for task in self.tasks.tasks().iter().filter(|task| !task.completed) {
+                        ui.selectable_value(&mut selected, Some(task.id), &task.name);
+                    }
+                });
+        });
+
+        if selected != active {
+            self.set_active_task(selected);
+        }
+    }
+
+    fn show_tasks_panel(&mut self, ui: &mut egui::Ui) {
+        let mut changed = false;
+        ui.group(|ui| {
+            ui.vertical(|ui| {
+                ui.label(RichText::new("Tasks").size(20.0).strong());
+                ui.add_space(10.0);
+
+                ui.horizontal(|ui| {
+                    ui.add(egui::TextEdit::singleline(&mut self.new_task_name).hint_text("New task").desired_width(200.0));
+                    ui.add(egui::DragValue::new(&mut self.new_task_estimate).clamp_range(1..=20).suffix(" 🍅"));
+                    if ui.button("Add").clicked() && !self.new_task_name.trim().is_empty() {
+                        self.tasks.add(&self.new_task_name, self.new_task_estimate);
+                        self.new_task_name.clear();
+                        self.new_task_estimate = 1;
+                        changed = true;
+                    }
+                });
+                ui.add_space(5.0);
+
+                let mut rename: Option<(u64, String)> = None;
+                let mut completion: Option<(u64, bool)> = None;
+                let mut estimate: Option<(u64, u32)> = None;
+
+                egui::ScrollArea::vertical().max_height(200.0).show(ui, |ui| {
+                    for task in self.tasks.tasks() {
+                        ui.horizontal(|ui| {
+                            let mut completed = task.completed;
+                            if ui.checkbox(&mut completed, "").changed() {
+                                completion = Some((task.id, completed));
+                            }
+
+                            match &mut self.renaming_task {
+                                Some((id, name)) if *id == task.id => {
+                                    let response = ui.text_edit_singleline(name);
+                                    if response.lost_focus() || ui.button("✔").clicked() {
+                                        rename = Some((*id, name.clone()));
+                                    }
+                                }
+                                _ => {
+                                    let name = if task.completed {
+                                        RichText::new(&task.name).strikethrough().weak()
+                                    } else {
+                                        RichText::new(&task.name)
+                                    };
+                                    ui.label(name);
+                                    if ui.small_button("✏").clicked() {
+                                        self.renaming_task = Some((task.id, task.name.clone()));
+                                    }
+                                }
+                            }
+
+                            let mut estimated = task.estimated_pomodoros;
+                            ui.label(format!("🍅 {} /", task.actual_pomodoros));
+                            if ui.add(egui::DragValue::new(&mut estimated).clamp_range(1..=20)).changed() {
+                                estimate = Some((task.id, estimated));
+                            }
+                        });
+                    }
+                });
+
+                if let Some((id, name)) = rename {
+                    if !name.trim().is_empty() {
+                        self.tasks.rename(id, &name);
+                    }
+                    self.renaming_task = None;
+                    changed = true;
+                }
+                if let Some((id, completed)) = completion {
+                    self.tasks.set_completed(id, completed);
+                    changed = true;
+                }
+                if let Some((id, estimated)) = estimate {
+                    self.tasks.set_estimate(id, estimated);
+                    changed = true;
+                }
+            });
+        });
+
+        if changed {
+            // Keep the timer's copy of the active task in step with renames and completions
+            self.timer.lock().unwrap().set_task(self.tasks.active().map(|task| task.tag()));
+            self.save_tasks();
+        }
+    }
+
+    // Interruption logging and void, shown only during work sessions
+    fn show_work_session_tools(&mut self, ui: &mut egui::Ui) {
+        let status = self.timer.lock().unwrap().status();
+        let (is_work, count) = (status.session_type == SessionType::Work, status.interruptions);
+        if !is_work {
+            return;
+        }
+
+        ui.horizontal(|ui| {
+            ui.label(format!("Interruptions ({}):", count));
+            ui.add(egui::TextEdit::singleline(&mut self.interruption_note).hint_text("note").desired_width(90.0));
+            let mut kind = None;
+            if ui.button("Internal").clicked() {
+                kind = Some(InterruptionKind::Internal);
+            }
+            if ui.button("External").clicked() {
+                kind = Some(InterruptionKind::External);
+            }
+            ui.checkbox(&mut self.pause_on_interruption, "pause");
+
+            if let Some(kind) = kind {
+                let note = Some(std::mem::take(&mut self.interruption_note));
+                self.timer.lock().unwrap().interrupt(kind, note, self.pause_on_interruption);
+            }
+        });
+
+        ui.horizontal(|ui| {
+            ui.label("Abandon:");
+            ui.add(egui::TextEdit::singleline(&mut self.void_reason).hint_text("reason").desired_width(120.0));
+            if ui.button("✖ Void").on_hover_text("Start this pomodoro over without counting it").clicked() {
+                let reason = Some(std::mem::take(&mut self.void_reason));
+                self.timer.lock().unwrap().void(reason);
+            }
+        });
+    }
+
+    fn show_stats_panel(&mut self, ui: &mut egui::Ui) {
+        let stats = &self.stats;
+        let mut export_format = None;
+        ui.group(|ui| {
+            ui.vertical(|ui| {
+                ui.label(RichText::new("Statistics").size(20.0).strong());
+                ui.add_space(10.0);
+
+                ui.label(format!("Pomodoros today: {}", stats.pomodoros_today));
+                ui.label(format!("Pomodoros this week: {}", stats.pomodoros_this_week));
+                ui.label(format!("Total focus time: {} min", stats.total_focus_minutes));
+                ui.label(format!("Longest streak: {} days", stats.longest_streak_days));
+                let ratio = match stats.completion_ratio() {
+                    Some(ratio) => format!("{:.0}%", ratio * 100.0),
+                    None => "–".to_string(),
+                };
+                ui.label(format!(
+                    "Completed vs skipped: {} / {} ({})",
+                    stats.completed, stats.skipped, ratio
+                ));
+                ui.label(format!("Voided: {}", stats.voided));
+                if self.config.daily_goal > 0 {
+                    ui.label(format!("Daily goal streak: {} days", self.goal_streak));
+                }
+                ui.label(format!(
+                    "Interruptions today: {} internal, {} external",
+                    stats.internal_interruptions_today, stats.external_interruptions_today
+                ));
+                ui.add_space(10.0);
+
+                // Bar chart of the last 7 days, drawn like the progress bar
+                let chart_width = ui.available_width();
+                let chart_rect = ui.allocate_space(Vec2::new(chart_width, CHART_HEIGHT + 20.0)).1;
+                let max = stats.last_days.iter().copied().max().unwrap_or(0).max(1) as f32;
+                let slot = chart_width / stats::CHART_DAYS as f32;
+                let today = Local::now().date_naive();
+
+                for (i, &count) in stats.last_days.iter().enumerate() {
+                    let x = chart_rect.min.x + slot * i as f32;
+                    let bar_height = CHART_HEIGHT * count as f32 / max;
+                    let bar_bottom = chart_rect.min.y + CHART_HEIGHT;
+                    let bar_rect = Rect::from_min_max(
+                        Pos2::new(x + slot * 0.2, bar_bottom - bar_height),
+                        Pos2::new(x + slot * 0.8, bar_bottom),
+                    );
+                    ui.painter().rect_filled(
+                        Rect::from_min_max(Pos2::new(bar_rect.min.x, chart_rect.min.y), bar_rect.max),
+                        3.0,
+                        Color32::from_gray(50),
+                    );
+                    ui.painter().rect_filled(bar_rect, 3.0, Color32::from_rgb(255, 99, 71));
+
+                    let day = today - chrono::Duration::days((stats::CHART_DAYS - 1 - i) as i64);
+                    ui.painter().text(
+                        Pos2::new(x + slot / 2.0, bar_bottom + 10.0),
+                        Align2::CENTER_CENTER,
+                        day.format("%a").to_string(),
+                        FontId::proportional(12.0),
+                        Color32::GRAY,
+                    );
+                    if count > 0 {
+                        ui.painter().text(
+                            Pos2::new(x + slot / 2.0, bar_rect.min.y - 8.0),
+                            Align2::CENTER_CENTER,
+                            count.to_string(),
+                            FontId::proportional(12.0),
+                            Color32::WHITE,
+                        );
+                    }
+                }
+                ui.add_space(10.0);
+
+                ui.horizontal(|ui| {
+                    ui.add(egui::TextEdit::singleline(&mut self.export_from).hint_text("from YYYY-MM-DD").desired_width(110.0));
+                    ui.add(egui::TextEdit::singleline(&mut self.export_to).hint_text("to YYYY-MM-DD").desired_width(110.0));
+                    ui.add(egui::TextEdit::singleline(&mut self.export_task).hint_text("task").desired_width(90.0));
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Export:");
+                    for (format, label) in [
+                        (ExportFormat::Csv, "CSV"),
+                        (ExportFormat::Json, "JSON"),
+                        (ExportFormat::Ics, "Calendar"),
+                    ] {
+                        if ui.button(label).clicked() {
+                            export_format = Some(format);
+                        }
+                    }
+                });
+                if let Some(message) = &self.export_message {
+                    ui.label(RichText::new(message).size(12.0).color(Color32::GRAY));
+                }
+            });
+        });
+
+        if let Some(format) = export_format {
+            self.export_message = Some(self.export_history(format));
+        }
+    }
+
+    // The export inputs as a filter, or what's wrong with them
+    fn export_filter(&self) -> Result<ExportFilter, String> {
+        let date = |text: &str, name: &str| match text.trim() {
+            "" => Ok(None),
+            text => NaiveDate::parse_from_str(text, "%Y-%m-%d")
+                .map(Some)
+                .map_err(|_| format!("The {} date must be YYYY-MM-DD", name)),
+        };
+        let filter = ExportFilter {
+            from: date(&self.export_from, "from")?,
+            to: date(&self.export_to, "to")?,
+            task: Some(self.export_task.trim().to_string()).filter(|task| !task.is_empty()),
+        };
+        if let (Some(from), Some(to)) = (filter.from, filter.to) {
+            if from > to {
+                return Err("The from date is after the to date".to_string());
+            }
+        }
+        Ok(filter)
+    }
+
+    // Writes the chosen part of the history next to the user's downloads;
+    // returns what to show
+    fn export_history(&self, format: ExportFormat) -> String {
+        let filter = match self.export_filter() {
+            Ok(filter) => filter,
+            Err(message) => return message,
+        };
+        let Some(history) = &self.history else {
+            return "No data directory".to_string();
+        };
+        let Some(path) = export::default_path(format) else {
+            return "No folder to export to".to_string();
+        };
+        let result = filter
+            .records(history)
+            .and_then(|records| export::write_file(format, &records, &path));
+        match result {
+            Ok(()) => format!("Saved to {}", path.display()),
+            Err(e) => format!("Export failed: {}", e),
+        }
+    }
+
+    // The window's settings, except that durations left as they were last
+    // applied keep their saved values, so command-line ones aren't saved
+    fn settings_to_save(&self) -> Config {
+        let mut settings = self.config.clone();
+        let (launch, saved) = (&self.launch_config, &self.saved_config);
+        for (value, launched, kept) in [
+            (&mut settings.work_minutes, launch.work_minutes, saved.work_minutes),
+            (&mut settings.short_break_minutes, launch.short_break_minutes, saved.short_break_minutes),
+            (&mut settings.long_break_minutes, launch.long_break_minutes, saved.long_break_minutes),
+            (&mut settings.cycle_length, launch.cycle_length, saved.cycle_length),
+        ] {
+            if *value == launched {
+                *value = kept;
+            }
+        }
+        if settings.custom_sequence == launch.custom_sequence {
+            settings.custom_sequence = saved.custom_sequence.clone();
+        }
+        settings
+    }
+
+    fn save_snapshot(&mut self) {
+        self.last_snapshot = Instant::now();
+        let mut timer = self.timer.lock().unwrap();
+        if let Err(e) = SavedSession::store(&mut timer) {
+            eprintln!("Failed to save session state: {}", e);
+        }
+    }
+
+    fn show_resume_prompt(&mut self, ctx: &egui::Context) {
+        let Some(saved) = &self.pending_resume else {
+            return;
+        };
+
+        let remaining = saved.timer.time_remaining.as_secs();
+        let state = if saved.timer.is_running { "running" } else { "paused" };
+        let description = format!(
+            "{} with {:02}:{:02} left ({}) when the timer closed at {}.",
+            saved.timer.session_type.name(),
+            remaining / 60,
+            remaining % 60,
+            state,
+            saved.saved_at.format("%H:%M"),
+        );
+
+        let mut resume = false;
+        let mut discard = false;
+        egui::Window::new("Resume previous session?")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, Vec2::ZERO)
+            .show(ctx, |ui| {
+                ui.label(description);
+                ui.add_space(10.0);
+                ui.horizontal(|ui| {
+                    resume = ui.button("Resume").clicked();
+                    discard = ui.button("Discard").clicked();
+                });
+            });
+
+        if resume {
+            let saved = self.pending_resume.take().unwrap();
+            self.timer.lock().unwrap().restore(&saved.timer, saved.time_away());
+            self.save_snapshot();
+        } else if discard {
+            self.pending_resume = None;
+            if let Err(e) = SavedSession::clear() {
+                eprintln!("Failed to discard session state: {}", e);
+            }
+        }
+    }
+
+    fn show_tomato_overlay(&mut self, caption: Option<String>) {
         #[cfg(debug_assertions)]
         println!("Triggering tomato overlay animation...");
         
         // Spawn a separate process to show the overlay
         // This avoids the issue of running two eframe event loops in the same process
         let exe = std::env::current_exe().unwrap();
-        let result = std::process::Command::new(exe)
-            .arg("--overlay")
-            .spawn();
+        let mut command = std::process::Command::new(exe);
+        command.arg("overlay");
+        if let Some(caption) = caption {
+            command.arg("--caption").arg(caption);
+        }
+        let result = command.spawn();
             
         #[cfg(debug_assertions)]
         match result {
@@ -63,18 +608,9 @@ impl PomodoroApp {
         }
     }
 
-    fn send_notification(&self, session_type: &SessionType) {
-        let message = match session_type {
-            SessionType::Work => "Work session completed! Time for a break.",
-            SessionType::ShortBreak => "Break finished! Ready to work?",
-            SessionType::LongBreak => "Long break finished! Let's get back to it!",
-        };
-
-        let _ = Notification::new()
-            .summary("Pomodoro Timer")
-            .body(message)
-            .timeout(5000)
-            .show();
+    fn send_notification(&self, ctx: &egui::Context, session_type: &SessionType) {
+        let ctx = ctx.clone();
+        notifications::session_finished(*session_type, &self.timer, move || ctx.request_repaint());
     }
 }
 
@@ -85,6 +621,13 @@ impl eframe::App for PomodoroApp {
         egui::Rgba::TRANSPARENT.to_array()
     }
 
+    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        self.record_finished_sessions();
+        if self.pending_resume.is_none() {
+            self.save_snapshot();
+        }
+    }
+
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
         // Request repaint for smooth timer updates
         ctx.request_repaint_after(Duration::from_millis(100));
@@ -92,17 +635,59 @@ impl eframe::App for PomodoroApp {
         // Update overlay
         // Overlay update removed - handled by separate window
 
+        // With a tray icon, closing the window only hides it, or minimises it
+        // where windows can't be hidden
+        if ctx.input(|i| i.viewport().close_requested())
+            && self.tray.is_some()
+            && !self.quitting.load(Ordering::SeqCst)
+        {
+            ctx.send_viewport_cmd(egui::ViewportCommand::CancelClose);
+            if can_hide_window() {
+                ctx.send_viewport_cmd(egui::ViewportCommand::Visible(false));
+            } else {
+                ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(true));
+            }
+        }
+
+        // Offer to resume a session left over from a previous run; until the
+        // user decides, don't overwrite it with the fresh timer's state
+        if self.pending_resume.is_some() {
+            self.show_resume_prompt(ctx);
+        } else if self.last_snapshot.elapsed() >= SNAPSHOT_INTERVAL {
+            self.save_snapshot();
+        }
+
+        if self.history_changed.swap(false, Ordering::SeqCst) && self.show_stats {
+            self.refresh_stats();
+        }
+
         // Check if timer completed
         {
             let mut timer = self.timer.lock().unwrap();
-            if timer.just_completed() {
-                let session_type = timer.get_session_type();
+            if let Some(session_type) = timer.just_completed() {
                 timer.clear_completed_flag();
                 drop(timer); // Explicitly drop the lock before calling methods that need &mut self
-                self.send_notification(&session_type);
-                self.show_tomato_overlay();
+                if self.config.notifications_enabled {
+                    self.send_notification(ctx, &session_type);
+                }
+                self.sound.session_finished(session_type, &self.config);
+                if self.config.overlay_enabled {
+                    // A session waiting to be started says so over the animation
+                    let timer = self.timer.lock().unwrap();
+                    let caption = timer.is_waiting().then(|| {
+                        format!("{} ready: press Start when you are", timer.get_session_type().name())
+                    });
+                    drop(timer);
+                    self.show_tomato_overlay(caption);
+                }
             }
         }
+        self.record_finished_sessions();
+
+        let timer = self.timer.lock().unwrap();
+        let (session_type, is_running) = (timer.get_session_type(), timer.is_running());
+        drop(timer);
+        self.sound.update_ticking(session_type, is_running, &self.config);
 
         // Main UI
         egui::CentralPanel::default().show(ctx, |ui| {
@@ -116,15 +701,19 @@ impl eframe::App for PomodoroApp {
                 // Timer display
                 let mut timer = self.timer.lock().unwrap();
                 let time_str = timer.get_time_string();
-                let session_str = match timer.get_session_type() {
-                    SessionType::Work => "Work Session",
-                    SessionType::ShortBreak => "Short Break",
-                    SessionType::LongBreak => "Long Break",
-                };
+                let session_str = timer.get_session_type().name();
+                let waiting = timer.is_waiting();
+                let overtime = timer.is_in_overtime();
                 drop(timer);
 
                 ui.label(RichText::new(time_str).size(64.0).strong());
                 ui.label(RichText::new(session_str).size(24.0));
+                if overtime {
+                    ui.label(RichText::new("Overtime: skip to take your break").color(Color32::from_rgb(255, 193, 7)));
+                }
+                if waiting {
+                    ui.label(RichText::new("Waiting for you: press Start when ready").color(Color32::from_rgb(255, 193, 7)));
+                }
                 ui.add_space(20.0);
 
                 // Progress bar
@@ -149,7 +738,11 @@ impl eframe::App for PomodoroApp {
                     5.0,
                     Color32::from_rgb(255, 99, 71),
                 );
-                ui.add_space(30.0);
+                ui.add_space(20.0);
+
+                // Task the next pomodoros count towards
+                self.show_task_picker(ui);
+                ui.add_space(10.0);
 
                 // Control buttons
                 ui.horizontal(|ui| {
@@ -176,29 +769,74 @@ impl eframe::App for PomodoroApp {
                     }
                 });
 
+                ui.horizontal(|ui| {
+                    if ui.button("+5 min").clicked() {
+                        self.timer.lock().unwrap().extend(ADJUST_STEP);
+                    }
+                    if ui.button("−5 min").clicked() {
+                        self.timer.lock().unwrap().shorten(ADJUST_STEP);
+                    }
+                });
+
+                self.show_work_session_tools(ui);
+
                 ui.add_space(20.0);
 
                 // Session info
                 let timer = self.timer.lock().unwrap();
                 let session_count = timer.get_session_count();
+                let cycle_length = timer.get_cycle_length();
                 drop(timer);
-                ui.label(format!("Session {} of 4", session_count));
-
-                ui.add_space(40.0);
+                ui.label(format!("Session {} of {}", session_count, cycle_length));
 
-                // Settings toggle
-                if ui.button(RichText::new("⚙ Settings").size(18.0)).clicked() {
-                    self.show_settings = !self.show_settings;
-                    
-                    // Resize window based on settings visibility
-                    let new_height = if self.show_settings {
-                        WINDOW_HEIGHT_EXPANDED
+                // Goal progress, by the daily goal if set, else the weekly one
+                if let Some(goal) = self.timer.lock().unwrap().status().goal {
+                    let (done, target) = if goal.daily_goal > 0 {
+                        (goal.today, goal.daily_goal)
                     } else {
-                        WINDOW_HEIGHT_COLLAPSED
+                        (goal.this_week, goal.weekly_goal)
                     };
-                    
-                    // Request window resize through context
-                    ctx.send_viewport_cmd(egui::ViewportCommand::InnerSize(Vec2::new(WINDOW_WIDTH, new_height)));
+                    ui.add(
+                        egui::ProgressBar::new((done as f32 / target as f32).min(1.0))
+                            .desired_width(available_width * 0.6)
+                            .fill(Color32::from_rgb(76, 175, 80))
+                            .text(format!("Goal: {}", goal.summary())),
+                    );
+                }
+
+                ui.add_space(40.0);
+
+                // Settings and stats toggles
+                ui.horizontal(|ui| {
+                    if ui.button(RichText::new("⚙ Settings").size(18.0)).clicked() {
+                        self.show_settings = !self.show_settings;
+                        self.resize_window(ctx);
+                    }
+
+                    if ui.button(RichText::new("📊 Stats").size(18.0)).clicked() {
+                        self.show_stats = !self.show_stats;
+                        if self.show_stats {
+                            self.refresh_stats();
+                        }
+                        self.resize_window(ctx);
+                    }
+
+                    if ui.button(RichText::new("📝 Tasks").size(18.0)).clicked() {
+                        self.show_tasks = !self.show_tasks;
+                        self.resize_window(ctx);
+                    }
+                });
+
+                // Tasks panel
+                if self.show_tasks {
+                    ui.add_space(20.0);
+                    self.show_tasks_panel(ui);
+                }
+
+                // Stats panel
+                if self.show_stats {
+                    ui.add_space(20.0);
+                    self.show_stats_panel(ui);
                 }
 
                 // Settings panel
@@ -209,28 +847,112 @@ impl eframe::App for PomodoroApp {
                             ui.label(RichText::new("Timer Settings").size(20.0).strong());
                             ui.add_space(10.0);
 
-                            ui.horizontal(|ui| {
-                                ui.label("Work Duration (min):");
-                                ui.add(egui::Slider::new(&mut self.work_duration, 1..=60));
-                            });
+                            ui.checkbox(&mut self.use_custom_sequence, "Custom sequence");
+                            ui.add_space(5.0);
+
+                            if self.use_custom_sequence {
+                                ui.label("Steps of W (work), S (short break), L (long break) and minutes:");
+                                ui.text_edit_singleline(&mut self.custom_sequence);
+                            } else {
+                                ui.horizontal(|ui| {
+                                    ui.label("Work Duration (min):");
+                                    ui.add(egui::Slider::new(&mut self.config.work_minutes, 1..=60));
+                                });
+
+                                ui.horizontal(|ui| {
+                                    ui.label("Short Break (min):");
+                                    ui.add(egui::Slider::new(&mut self.config.short_break_minutes, 1..=30));
+                                });
 
+                                ui.horizontal(|ui| {
+                                    ui.label("Long Break (min):");
+                                    ui.add(egui::Slider::new(&mut self.config.long_break_minutes, 1..=60));
+                                });
+
+                                ui.horizontal(|ui| {
+                                    ui.label("Sessions per Cycle:");
+                                    ui.add(egui::Slider::new(&mut self.config.cycle_length, 1..=12));
+                                });
+                            }
+
+                            ui.add_space(5.0);
+                            ui.checkbox(&mut self.config.notifications_enabled, "Desktop notifications");
+                            ui.checkbox(&mut self.config.overlay_enabled, "Tomato overlay animation");
+                            ui.checkbox(&mut self.config.auto_start_breaks, "Start breaks automatically");
+                            ui.checkbox(&mut self.config.auto_start_work, "Start work automatically after breaks");
+                            ui.checkbox(&mut self.config.flow_mode, "Flow mode: keep counting past the end of work");
+                            ui.add_enabled_ui(self.config.flow_mode, |ui| {
+                                ui.checkbox(&mut self.config.overtime_to_break, "Add overtime to the next break");
+                            });
+                            ui.checkbox(&mut self.config.tray_enabled, "Tray icon (closing hides the window; needs restart)");
+                            // Without the `sound` feature there is nothing to play with
+                            ui.add_enabled_ui(sound::BUILT_IN, |ui| {
+                                let label = if sound::BUILT_IN { "Sound alerts" } else { "Sound alerts (not in this build)" };
+                                ui.checkbox(&mut self.config.sound_enabled, label);
+                            });
+                            ui.add_enabled_ui(sound::BUILT_IN && self.config.sound_enabled, |ui| {
+                                ui.horizontal(|ui| {
+                                    ui.label("Volume (%):");
+                                    ui.add(egui::Slider::new(&mut self.config.sound_volume, 0..=100));
+                                });
+                                ui.checkbox(&mut self.config.ticking_enabled, "Ticking during work sessions");
+                            });
                             ui.horizontal(|ui| {
-                                ui.label("Short Break (min):");
-                                ui.add(egui::Slider::new(&mut self.short_break, 1..=30));
+                                ui.label("Daily goal (0 = none):");
+                                ui.add(egui::Slider::new(&mut self.config.daily_goal, 0..=24));
                             });
-
                             ui.horizontal(|ui| {
-                                ui.label("Long Break (min):");
-                                ui.add(egui::Slider::new(&mut self.long_break, 1..=60));
+                                ui.label("Weekly goal (0 = none):");
+                                ui.add(egui::Slider::new(&mut self.config.weekly_goal, 0..=120));
                             });
 
+                            if let Some(error) = &self.sequence_error {
+                                ui.colored_label(Color32::from_rgb(255, 99, 71), error);
+                            }
+
                             if ui.button("Apply Settings").clicked() {
-                                let mut timer = self.timer.lock().unwrap();
-                                timer.update_durations(
-                                    self.work_duration,
-                                    self.short_break,
-                                    self.long_break,
-                                );
+                                let sequence = if self.use_custom_sequence {
+                                    SessionSequence::parse(&self.custom_sequence)
+                                } else {
+                                    Ok(SessionSequence::classic(
+                                        self.config.work_minutes,
+                                        self.config.short_break_minutes,
+                                        self.config.long_break_minutes,
+                                        self.config.cycle_length,
+                                    ))
+                                };
+
+                                match sequence {
+                                    Ok(sequence) => {
+                                        self.custom_sequence = sequence.to_string();
+                                        self.config.custom_sequence = if self.use_custom_sequence {
+                                            Some(self.custom_sequence.clone())
+                                        } else {
+                                            None
+                                        };
+                                        let mut timer = self.timer.lock().unwrap();
+                                        timer.set_sequence(sequence);
+                                        timer.set_auto_start(self.config.auto_start_breaks, self.config.auto_start_work);
+                                        timer.set_flow_mode(self.config.flow_mode, self.config.overtime_to_break);
+                                        timer.set_goals(goals::tracker(
+                                            &self.config,
+                                            self.history.as_ref(),
+                                            Local::now().date_naive(),
+                                        ));
+                                        drop(timer);
+                                        self.sequence_error = None;
+
+                                        let settings = self.settings_to_save();
+                                        match settings.save() {
+                                            Ok(()) => {
+                                                self.saved_config = settings;
+                                                self.launch_config = self.config.clone();
+                                            }
+                                            Err(e) => eprintln!("Failed to save settings: {}", e),
+                                        }
+                                    }
+                                    Err(e) => self.sequence_error = Some(format!("Invalid sequence: {}", e)),
+                                }
                             }
                         });
                     });
@@ -241,18 +963,80 @@ impl eframe::App for PomodoroApp {
 }
 
 pub fn run() -> Result<(), eframe::Error> {
-    run_app()
+    run_app(Config::load(), false)
+}
+
+enum Frontend {
+    Window,
+    Headless,
+    Terminal,
+}
+
+// winit (through eframe) can't hide windows on Wayland: set_visible does
+// nothing there. It picks Wayland whenever a Wayland display is available,
+// unless told to use X11.
+fn can_hide_window() -> bool {
+    if !cfg!(target_os = "linux") {
+        return true;
+    }
+    std::env::var_os("WAYLAND_DISPLAY").is_none()
+        || std::env::var("WINIT_UNIX_BACKEND").is_ok_and(|backend| backend == "x11")
 }
 
 fn main() -> Result<(), eframe::Error> {
-    // Check if we're being launched as an overlay process
-    let args: Vec<String> = std::env::args().collect();
-    if args.len() > 1 && args[1] == "--overlay" {
-        // Run the overlay and exit
-        transparent_overlay::TransparentOverlay::show();
-        return Ok(());
+    let cli = Cli::parse();
+    let mut config = Config::load();
+    let mut autostart = false;
+
+    // What to ask of an instance that is already running
+    let (frontend, forwarded) = match cli.command {
+        // Launched as an overlay process by the main window
+        Some(Command::Overlay { caption }) => {
+            transparent_overlay::TransparentOverlay::show(caption);
+            return Ok(());
+        }
+        Some(Command::Start { overrides, headless }) => {
+            overrides.apply(&mut config);
+            autostart = true;
+            let mut forwarded = Vec::new();
+            if !overrides.is_empty() {
+                forwarded.push(overrides.to_request());
+            }
+            forwarded.push(Request::Start);
+            let frontend = if headless || cli.headless { Frontend::Headless } else { Frontend::Window };
+            (frontend, forwarded)
+        }
+        Some(Command::Tui) => (Frontend::Terminal, vec![Request::Focus]),
+        Some(command) => cli::run(command),
+        None if cli.headless => (Frontend::Headless, vec![Request::Focus]),
+        None => (Frontend::Window, vec![Request::Focus]),
+    };
+
+    // Held until exit; a second launch forwards its action and quits
+    let _instance = match InstanceLock::acquire() {
+        Ok(Some(lock)) => Some(lock),
+        Ok(None) => cli::forward(&forwarded),
+        Err(e) => {
+            eprintln!("Failed to check for a running instance: {}", e);
+            None
+        }
+    };
+
+    match frontend {
+        Frontend::Headless => {
+            headless::run(config);
+            return Ok(());
+        }
+        Frontend::Terminal => {
+            if let Err(e) = tui::run(config) {
+                eprintln!("Terminal interface failed: {}", e);
+                std::process::exit(1);
+            }
+            return Ok(());
+        }
+        Frontend::Window => {}
     }
-    
+
     // On Windows, allocate a console for debugging only in debug builds
     #[cfg(all(windows, debug_assertions))]
     {
@@ -263,7 +1047,7 @@ fn main() -> Result<(), eframe::Error> {
         }
     }
     
-    let result = run();
+    let result = run_app(config, autostart);
     
     // If there was an error in debug mode, keep console open on Windows
     #[cfg(all(windows, debug_assertions))]
@@ -276,7 +1060,7 @@ fn main() -> Result<(), eframe::Error> {
     result
 }
 
-fn run_app() -> Result<(), eframe::Error> {
+fn run_app(config: Config, autostart: bool) -> Result<(), eframe::Error> {
     // Check transparency support
     if !check_transparency::check_transparency_support() {
         println!("WARNING: Transparency may not be supported");
@@ -294,7 +1078,62 @@ fn run_app() -> Result<(), eframe::Error> {
     eframe::run_native(
         "Rust Pomodoro Timer",
         options,
-        Box::new(|_cc| Box::new(PomodoroApp::default())),
+        Box::new(move |cc| {
+            let mut app = PomodoroApp::new(config);
+            if autostart {
+                // An explicit start replaces whatever was saved last time
+                app.pending_resume = None;
+                app.timer.lock().unwrap().start();
+            }
+            #[cfg(unix)]
+            {
+                let ctx = cc.egui_ctx.clone();
+                let history_changed = app.history_changed.clone();
+                let on_request = move |request: &Request| {
+                    match request {
+                        Request::Focus => {
+                            ctx.send_viewport_cmd(egui::ViewportCommand::Visible(true));
+                            ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(false));
+                            ctx.send_viewport_cmd(egui::ViewportCommand::Focus);
+                        }
+                        Request::HistoryChanged => history_changed.store(true, Ordering::SeqCst),
+                        _ => {}
+                    }
+                    ctx.request_repaint();
+                };
+                app._control = control::ControlServer::start(app.timer.clone(), on_request)
+                    .map_err(|e| eprintln!("Control socket unavailable: {}", e))
+                    .ok();
+            }
+            #[cfg(target_os = "linux")]
+            {
+                let ctx = cc.egui_ctx.clone();
+                app._dbus = dbus::DbusService::start(app.timer.clone(), move || ctx.request_repaint())
+                    .map_err(|e| eprintln!("D-Bus service unavailable: {}", e))
+                    .ok();
+            }
+            if app.config.tray_enabled {
+                let ctx = cc.egui_ctx.clone();
+                let quitting = app.quitting.clone();
+                let on_event = move |event: TrayEvent| {
+                    match event {
+                        TrayEvent::TimerChanged => {}
+                        TrayEvent::ShowWindow => {
+                            ctx.send_viewport_cmd(egui::ViewportCommand::Visible(true));
+                            ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(false));
+                            ctx.send_viewport_cmd(egui::ViewportCommand::Focus);
+                        }
+                        TrayEvent::Quit => {
+                            quitting.store(true, Ordering::SeqCst);
+                            ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+                        }
+                    }
+                    ctx.request_repaint();
+                };
+                app.tray = TrayManager::start(app.timer.clone(), on_event);
+            }
+            Box::new(app)
+        }),
     )
 }
 
@@ -0,0 +1,167 @@
+// Desktop notifications shared by the window and the terminal front ends.
+//
+// Where the notification server supports actions (most Linux desktops), the
+// end-of-session notification carries buttons that act on the timer: start
+// the next session, skip a break or take five more minutes of it. Servers
+// without actions, and other platforms, get the plain notification.
+
+use notify_rust::Notification;
+use std::sync::{Arc, Mutex};
+
+use crate::timer::{PomodoroTimer, SessionType};
+
+const SUMMARY: &str = "Pomodoro Timer";
+
+/// A button on the end-of-session notification.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(not(all(unix, not(target_os = "macos"))), allow(dead_code))]
+enum Action {
+    Start,
+    Skip,
+    Extend,
+}
+
+#[cfg_attr(not(all(unix, not(target_os = "macos"))), allow(dead_code))]
+impl Action {
+    const ALL: [Action; 3] = [Action::Start, Action::Skip, Action::Extend];
+
+    fn id(self) -> &'static str {
+        match self {
+            Action::Start => "start",
+            Action::Skip => "skip",
+            Action::Extend => "extend",
+        }
+    }
+
+    fn label(self, next: SessionType) -> &'static str {
+        match (self, next) {
+            (Action::Start, SessionType::Work) => "Start work",
+            (Action::Start, _) => "Start break",
+            (Action::Skip, _) => "Skip break",
+            (Action::Extend, _) => "+5 min",
+        }
+    }
+
+    fn apply(self, timer: &mut PomodoroTimer) {
+        match self {
+            Action::Start => timer.start(),
+            Action::Skip => timer.skip(),
+            Action::Extend => timer.extend(crate::ADJUST_STEP),
+        }
+    }
+}
+
+/// The buttons worth offering once `next` has taken over. Breaks can be
+/// skipped or lengthened; anything not yet running can be started.
+#[cfg_attr(not(all(unix, not(target_os = "macos"))), allow(dead_code))]
+fn actions_for(next: SessionType, next_running: bool) -> Vec<Action> {
+    let mut actions = Vec::new();
+    if !next_running {
+        actions.push(Action::Start);
+    }
+    if next != SessionType::Work {
+        actions.extend([Action::Skip, Action::Extend]);
+    }
+    actions
+}
+
+fn message(finished: SessionType) -> &'static str {
+    match finished {
+        SessionType::Work => "Work session completed! Time for a break.",
+        SessionType::ShortBreak => "Break finished! Ready to work?",
+        SessionType::LongBreak => "Long break finished! Let's get back to it!",
+    }
+}
+
+/// Announces the end of a session of the given type. Runs on its own thread,
+/// so the caller may still hold the timer lock. `on_action` is called after
+/// a button has changed the timer, e.g. to repaint the window.
+pub fn session_finished(
+    finished: SessionType,
+    timer: &Arc<Mutex<PomodoroTimer>>,
+    on_action: impl Fn() + Send + 'static,
+) {
+    let timer = timer.clone();
+    std::thread::spawn(move || {
+        #[cfg(all(unix, not(target_os = "macos")))]
+        if supports_actions() {
+            show_with_actions(finished, &timer, on_action);
+            return;
+        }
+
+        let _ = (timer, on_action);
+        // Without a notification daemon (e.g. over SSH) this fails; the caller
+        // has already reported the transition some other way
+        let _ = Notification::new()
+            .summary(SUMMARY)
+            .body(message(finished))
+            .timeout(5000)
+            .show();
+    });
+}
+
+/// Celebrates reaching a daily or weekly goal.
+pub fn goal_reached(message: &str) {
+    let body = format!("🎉 {}", message);
+    std::thread::spawn(move || {
+        let _ = Notification::new().summary(SUMMARY).body(&body).timeout(8000).show();
+    });
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn supports_actions() -> bool {
+    notify_rust::get_capabilities().is_ok_and(|capabilities| capabilities.iter().any(|c| c == "actions"))
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn show_with_actions(finished: SessionType, timer: &Mutex<PomodoroTimer>, on_action: impl Fn()) {
+    let (next, next_running, position) = {
+        let timer = timer.lock().unwrap();
+        (timer.get_session_type(), timer.is_running(), timer.get_session_count())
+    };
+
+    let mut notification = Notification::new();
+    notification
+        .summary(SUMMARY)
+        .body(message(finished))
+        // Long enough to reach for a button
+        .timeout(15_000);
+    for action in actions_for(next, next_running) {
+        notification.action(action.id(), action.label(next));
+    }
+    let Ok(handle) = notification.show() else {
+        return;
+    };
+
+    handle.wait_for_action(|id| {
+        let Some(action) = Action::ALL.into_iter().find(|action| action.id() == id) else {
+            // Dismissed, timed out or clicked on the body
+            return;
+        };
+        let mut timer = timer.lock().unwrap();
+        // A click on an old notification must not act on a later session
+        if timer.get_session_type() != next || timer.get_session_count() != position {
+            return;
+        }
+        action.apply(&mut timer);
+        drop(timer);
+        on_action();
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn breaks_offer_skip_and_extend_and_idle_sessions_offer_start() {
+        assert_eq!(actions_for(SessionType::ShortBreak, true), [Action::Skip, Action::Extend]);
+        assert_eq!(
+            actions_for(SessionType::LongBreak, false),
+            [Action::Start, Action::Skip, Action::Extend]
+        );
+        assert_eq!(actions_for(SessionType::Work, false), [Action::Start]);
+        assert!(actions_for(SessionType::Work, true).is_empty());
+        assert_eq!(Action::Start.label(SessionType::ShortBreak), "Start break");
+    }
+}
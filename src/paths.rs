@@ -0,0 +1,44 @@
+// Locations of the files the app keeps between runs.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+const APP_DIR: &str = "rust-pomodoro";
+
+/// A file in the per-user config directory (e.g. ~/.config/rust-pomodoro).
+pub fn config_file(name: &str) -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join(APP_DIR).join(name))
+}
+
+/// A file in the per-user data directory (e.g. ~/.local/share/rust-pomodoro).
+pub fn data_file(name: &str) -> Option<PathBuf> {
+    dirs::data_local_dir().map(|dir| dir.join(APP_DIR).join(name))
+}
+
+/// A per-user file belonging to the running instance, such as its socket or
+/// lock ($XDG_RUNTIME_DIR/rust-pomodoro.<extension> on Linux).
+pub fn runtime_file(extension: &str) -> PathBuf {
+    match dirs::runtime_dir() {
+        Some(dir) => dir.join(format!("{}.{}", APP_DIR, extension)),
+        // Without a per-user runtime directory, keep users apart by name
+        None => {
+            let user = std::env::var("USER")
+                .or_else(|_| std::env::var("USERNAME"))
+                .unwrap_or_default();
+            std::env::temp_dir().join(format!("{}-{}.{}", APP_DIR, user, extension))
+        }
+    }
+}
+
+/// Replaces `path` via a temporary file so a crash never leaves it truncated.
+pub fn write_atomic(path: &Path, contents: &[u8]) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    fs::write(&tmp, contents)?;
+    fs::rename(tmp, path)
+}
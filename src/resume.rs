@@ -0,0 +1,77 @@
+// Snapshot of the in-progress session, written periodically and on exit so it
+// can be offered for resumption after the app is closed or crashes.
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::paths;
+use crate::timer::{PomodoroTimer, TimerSnapshot};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedSession {
+    pub saved_at: DateTime<Local>,
+    pub timer: TimerSnapshot,
+}
+
+impl SavedSession {
+    pub fn capture(timer: &mut PomodoroTimer) -> Self {
+        Self {
+            saved_at: Local::now(),
+            timer: timer.snapshot(),
+        }
+    }
+
+    pub fn path() -> Option<PathBuf> {
+        paths::data_file("session.json")
+    }
+
+    /// Returns the saved session if there is one worth resuming.
+    pub fn load() -> Option<Self> {
+        let path = Self::path()?;
+        let text = fs::read_to_string(&path).ok()?;
+        match serde_json::from_str::<Self>(&text) {
+            Ok(saved) if saved.timer.is_in_progress() => Some(saved),
+            Ok(_) => None,
+            Err(e) => {
+                eprintln!("Ignoring invalid session file {}: {}", path.display(), e);
+                None
+            }
+        }
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let path = Self::path()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no data directory"))?;
+        let json = serde_json::to_string(self)?;
+        paths::write_atomic(&path, json.as_bytes())
+    }
+
+    /// Saves the timer's state, or removes the file if nothing is in progress.
+    pub fn store(timer: &mut PomodoroTimer) -> io::Result<()> {
+        let saved = Self::capture(timer);
+        if saved.timer.is_in_progress() {
+            saved.save()
+        } else {
+            Self::clear()
+        }
+    }
+
+    pub fn clear() -> io::Result<()> {
+        let Some(path) = Self::path() else {
+            return Ok(());
+        };
+        match fs::remove_file(path) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        }
+    }
+
+    /// Wall-clock time since the snapshot was taken.
+    pub fn time_away(&self) -> Duration {
+        (Local::now() - self.saved_at).to_std().unwrap_or(Duration::ZERO)
+    }
+}
@@ -0,0 +1,177 @@
+// User-defined session rotations such as "W25 S5 W25 S5 W50 L20".
+// Each step is a session type letter (W, S or L) followed by its length in minutes.
+
+use std::fmt;
+use std::time::Duration;
+
+use crate::timer::SessionType;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SessionStep {
+    pub session_type: SessionType,
+    pub minutes: u32,
+}
+
+impl SessionStep {
+    pub fn duration(&self) -> Duration {
+        Duration::from_secs(self.minutes as u64 * 60)
+    }
+}
+
+impl fmt::Display for SessionStep {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let letter = match self.session_type {
+            SessionType::Work => 'W',
+            SessionType::ShortBreak => 'S',
+            SessionType::LongBreak => 'L',
+        };
+        write!(f, "{}{}", letter, self.minutes)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SessionSequence {
+    steps: Vec<SessionStep>,
+}
+
+impl SessionSequence {
+    /// The traditional rotation: `cycle_length` work sessions separated by
+    /// short breaks, with a long break after the last one.
+    pub fn classic(work_min: u32, short_break_min: u32, long_break_min: u32, cycle_length: u32) -> Self {
+        let cycle_length = cycle_length.max(1);
+        let mut steps = Vec::with_capacity(cycle_length as usize * 2);
+        for i in 1..=cycle_length {
+            steps.push(SessionStep { session_type: SessionType::Work, minutes: work_min });
+            let (session_type, minutes) = if i == cycle_length {
+                (SessionType::LongBreak, long_break_min)
+            } else {
+                (SessionType::ShortBreak, short_break_min)
+            };
+            steps.push(SessionStep { session_type, minutes });
+        }
+        Self { steps }
+    }
+
+    pub fn parse(input: &str) -> Result<Self, String> {
+        let mut steps = Vec::new();
+        for token in input.split(|c: char| c.is_whitespace() || c == ',') {
+            if token.is_empty() {
+                continue;
+            }
+
+            let mut chars = token.chars();
+            let session_type = match chars.next().map(|c| c.to_ascii_uppercase()) {
+                Some('W') => SessionType::Work,
+                Some('S') => SessionType::ShortBreak,
+                Some('L') => SessionType::LongBreak,
+                _ => return Err(format!("'{}' must start with W, S or L", token)),
+            };
+            let minutes: u32 = chars
+                .as_str()
+                .parse()
+                .map_err(|_| format!("'{}' needs a length in minutes, e.g. W25", token))?;
+            if minutes == 0 {
+                return Err(format!("'{}' must last at least one minute", token));
+            }
+
+            steps.push(SessionStep { session_type, minutes });
+        }
+
+        if steps.is_empty() {
+            return Err("the sequence is empty".to_string());
+        }
+        if !steps.iter().any(|step| step.session_type == SessionType::Work) {
+            return Err("the sequence needs at least one work step (W)".to_string());
+        }
+        Ok(Self { steps })
+    }
+
+    pub fn len(&self) -> usize {
+        self.steps.len()
+    }
+
+    pub fn step(&self, index: usize) -> SessionStep {
+        self.steps[index % self.steps.len()]
+    }
+
+    /// Length of the first step of the given type, if the sequence has one.
+    pub fn minutes_of(&self, session_type: SessionType) -> Option<u32> {
+        self.steps
+            .iter()
+            .find(|step| step.session_type == session_type)
+            .map(|step| step.minutes)
+    }
+
+    /// Number of work sessions in one pass through the sequence.
+    pub fn cycle_length(&self) -> u32 {
+        self.steps
+            .iter()
+            .filter(|step| step.session_type == SessionType::Work)
+            .count() as u32
+    }
+
+    /// 1-based position of the work session at or before `index`, so a break
+    /// reports the work session it follows.
+    pub fn work_position(&self, index: usize) -> u32 {
+        let end = (index % self.steps.len()) + 1;
+        let count = self.steps[..end]
+            .iter()
+            .filter(|step| step.session_type == SessionType::Work)
+            .count() as u32;
+        count.max(1)
+    }
+}
+
+impl Default for SessionSequence {
+    fn default() -> Self {
+        Self::classic(25, 5, 15, 4)
+    }
+}
+
+impl fmt::Display for SessionSequence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, step) in self.steps.iter().enumerate() {
+            if i > 0 {
+                write!(f, " ")?;
+            }
+            write!(f, "{}", step)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_and_formats_round_trip() {
+        let sequence = SessionSequence::parse("W25 s5, w25 S5 W50 L20").unwrap();
+        assert_eq!(sequence.len(), 6);
+        assert_eq!(sequence.cycle_length(), 3);
+        assert_eq!(sequence.to_string(), "W25 S5 W25 S5 W50 L20");
+    }
+
+    #[test]
+    fn rejects_malformed_steps() {
+        assert!(SessionSequence::parse("").is_err());
+        assert!(SessionSequence::parse("X25").is_err());
+        assert!(SessionSequence::parse("W").is_err());
+        assert!(SessionSequence::parse("W0").is_err());
+    }
+
+    #[test]
+    fn rejects_sequences_without_work() {
+        assert!(SessionSequence::parse("S5 L15").is_err());
+        assert!(SessionSequence::parse("L15 W25").is_ok());
+    }
+
+    #[test]
+    fn classic_matches_traditional_rotation() {
+        let sequence = SessionSequence::classic(25, 5, 15, 3);
+        assert_eq!(sequence.to_string(), "W25 S5 W25 S5 W25 L15");
+        assert_eq!(sequence.work_position(0), 1);
+        assert_eq!(sequence.work_position(3), 2);
+        assert_eq!(sequence.work_position(5), 3);
+    }
+}
@@ -0,0 +1,210 @@
+// Audio cues: a chime when a session ends and optional ticking during work
+// sessions. The chimes are synthesised here so no sound files need shipping;
+// users can point the config at their own WAV or OGG files instead.
+//
+// Playback needs the `sound` feature (rodio, which links ALSA on Linux).
+// Without it, or when no audio device can be opened, the player is silent.
+
+use std::f32::consts::PI;
+
+use crate::config::Config;
+use crate::timer::SessionType;
+
+/// Whether this build can play sound at all.
+pub const BUILT_IN: bool = cfg!(feature = "sound");
+
+const SAMPLE_RATE: u32 = 44_100;
+const NOTE_SECS: f32 = 0.22;
+const CHIME_TAIL_SECS: f32 = 0.6;
+const TICK_SECS: f32 = 0.015;
+
+// Note frequencies in Hz
+const C5: f32 = 523.25;
+const E5: f32 = 659.25;
+const G5: f32 = 783.99;
+const C6: f32 = 1046.50;
+
+pub struct SoundPlayer {
+    #[cfg(feature = "sound")]
+    output: Option<backend::Output>,
+}
+
+impl SoundPlayer {
+    /// Opens the default audio device, falling back to silence without one.
+    pub fn new() -> Self {
+        Self {
+            #[cfg(feature = "sound")]
+            output: backend::Output::open()
+                .map_err(|e| eprintln!("No audio output, sounds are off: {}", e))
+                .ok(),
+        }
+    }
+
+    /// Plays the chime, or the user's file, for the end of a session.
+    pub fn session_finished(&self, finished: SessionType, config: &Config) {
+        if !config.sound_enabled {
+            return;
+        }
+        #[cfg(feature = "sound")]
+        if let Some(output) = &self.output {
+            let custom = match finished {
+                SessionType::Work => &config.work_sound,
+                SessionType::ShortBreak => &config.short_break_sound,
+                SessionType::LongBreak => &config.long_break_sound,
+            };
+            output.play_chime(finished, custom.as_deref(), volume(config));
+        }
+        #[cfg(not(feature = "sound"))]
+        let _ = finished;
+    }
+
+    /// Call on every update: ticks while a work session runs and ticking is on.
+    pub fn update_ticking(&mut self, session_type: SessionType, is_running: bool, config: &Config) {
+        let ticking = config.sound_enabled
+            && config.ticking_enabled
+            && is_running
+            && session_type == SessionType::Work;
+        #[cfg(feature = "sound")]
+        if let Some(output) = &mut self.output {
+            output.set_ticking(ticking, volume(config));
+        }
+        #[cfg(not(feature = "sound"))]
+        let _ = ticking;
+    }
+}
+
+#[cfg_attr(not(feature = "sound"), allow(dead_code))]
+fn volume(config: &Config) -> f32 {
+    config.sound_volume.min(100) as f32 / 100.0
+}
+
+// Work ending falls, a short break ending rises, a long break ending rises
+// a full octave so the cycle's end is easy to tell apart.
+#[cfg_attr(not(feature = "sound"), allow(dead_code))]
+fn chime_notes(finished: SessionType) -> &'static [f32] {
+    match finished {
+        SessionType::Work => &[E5, C5],
+        SessionType::ShortBreak => &[C5, E5, G5],
+        SessionType::LongBreak => &[C5, E5, G5, C6],
+    }
+}
+
+/// Mono samples of a soft bell-like chime, one note after another.
+#[cfg_attr(not(feature = "sound"), allow(dead_code))]
+fn chime(finished: SessionType) -> Vec<f32> {
+    let notes = chime_notes(finished);
+    let note_len = (NOTE_SECS * SAMPLE_RATE as f32) as usize;
+    let tail_len = (CHIME_TAIL_SECS * SAMPLE_RATE as f32) as usize;
+    let mut samples = vec![0.0; note_len * notes.len() + tail_len];
+
+    for (n, &frequency) in notes.iter().enumerate() {
+        // Each note rings on under the ones after it
+        for (i, sample) in samples[n * note_len..].iter_mut().enumerate() {
+            let t = i as f32 / SAMPLE_RATE as f32;
+            let envelope = (-t * 6.0).exp();
+            let tone = (2.0 * PI * frequency * t).sin() + 0.3 * (4.0 * PI * frequency * t).sin();
+            *sample += 0.25 * envelope * tone;
+        }
+    }
+    samples
+}
+
+/// One second holding a short click at the start, for looping.
+#[cfg_attr(not(feature = "sound"), allow(dead_code))]
+fn tick() -> Vec<f32> {
+    let click_len = (TICK_SECS * SAMPLE_RATE as f32) as usize;
+    let mut samples = vec![0.0; SAMPLE_RATE as usize];
+    for (i, sample) in samples[..click_len].iter_mut().enumerate() {
+        let t = i as f32 / SAMPLE_RATE as f32;
+        *sample = 0.15 * (-t * 400.0).exp() * (2.0 * PI * 1800.0 * t).sin();
+    }
+    samples
+}
+
+#[cfg(feature = "sound")]
+mod backend {
+    use rodio::buffer::SamplesBuffer;
+    use rodio::{Decoder, OutputStream, OutputStreamHandle, Sink, Source};
+    use std::fs::File;
+    use std::io::BufReader;
+
+    use super::{chime, tick, SAMPLE_RATE};
+    use crate::timer::SessionType;
+
+    pub struct Output {
+        // Dropping the stream stops all playback
+        _stream: OutputStream,
+        handle: OutputStreamHandle,
+        ticking: Option<Sink>,
+    }
+
+    impl Output {
+        pub fn open() -> Result<Self, rodio::StreamError> {
+            let (stream, handle) = OutputStream::try_default()?;
+            Ok(Self {
+                _stream: stream,
+                handle,
+                ticking: None,
+            })
+        }
+
+        pub fn play_chime(&self, finished: SessionType, custom: Option<&str>, volume: f32) {
+            let sink = match Sink::try_new(&self.handle) {
+                Ok(sink) => sink,
+                Err(e) => {
+                    eprintln!("Failed to play sound: {}", e);
+                    return;
+                }
+            };
+            sink.set_volume(volume);
+
+            let file = custom.map(|path| {
+                File::open(path)
+                    .map_err(|e| e.to_string())
+                    .and_then(|file| Decoder::new(BufReader::new(file)).map_err(|e| e.to_string()))
+                    .map_err(|e| eprintln!("Failed to play {}: {}", path, e))
+            });
+            match file {
+                Some(Ok(decoder)) => sink.append(decoder),
+                // A missing or unreadable file still gets the bundled chime
+                _ => sink.append(SamplesBuffer::new(1, SAMPLE_RATE, chime(finished))),
+            }
+            sink.detach();
+        }
+
+        pub fn set_ticking(&mut self, on: bool, volume: f32) {
+            if on && self.ticking.is_none() {
+                match Sink::try_new(&self.handle) {
+                    Ok(sink) => {
+                        sink.append(SamplesBuffer::new(1, SAMPLE_RATE, tick()).repeat_infinite());
+                        self.ticking = Some(sink);
+                    }
+                    Err(e) => eprintln!("Failed to play sound: {}", e),
+                }
+            } else if !on {
+                self.ticking = None;
+            }
+            if let Some(sink) = &self.ticking {
+                sink.set_volume(volume);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chimes_differ_per_session_and_stay_in_range() {
+        let work = chime(SessionType::Work);
+        let long_break = chime(SessionType::LongBreak);
+        assert!(long_break.len() > work.len());
+        assert!(work.iter().chain(&long_break).all(|s| s.abs() <= 1.0));
+        assert!(work.iter().any(|s| s.abs() > 0.1));
+
+        let tick = tick();
+        assert_eq!(tick.len(), SAMPLE_RATE as usize);
+        assert!(tick[SAMPLE_RATE as usize / 2..].iter().all(|&s| s == 0.0));
+    }
+}
@@ -0,0 +1,173 @@
+// Summary statistics over the session history for the stats panel.
+
+use chrono::{Datelike, Duration, NaiveDate};
+use std::collections::BTreeMap;
+
+use crate::history::{InterruptionKind, SessionOutcome, SessionRecord};
+use crate::timer::SessionType;
+
+pub const CHART_DAYS: usize = 7;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Stats {
+    pub pomodoros_today: u32,
+    pub pomodoros_this_week: u32,
+    pub total_focus_minutes: u64,
+    pub longest_streak_days: u32,
+    pub completed: u32,
+    pub skipped: u32,
+    /// Work sessions abandoned with void; never counted as pomodoros.
+    pub voided: u32,
+    /// Completed pomodoros per day, oldest first, ending today.
+    pub last_days: [u32; CHART_DAYS],
+    pub internal_interruptions_today: u32,
+    pub external_interruptions_today: u32,
+    /// Interruptions logged per day, lined up with `last_days`.
+    pub interruptions_last_days: [u32; CHART_DAYS],
+}
+
+impl Stats {
+    /// Only work sessions count; a pomodoro is a work session that ran to the end.
+    pub fn compute(records: &[SessionRecord], today: NaiveDate) -> Self {
+        let mut stats = Stats::default();
+        let mut per_day: BTreeMap<NaiveDate, u32> = BTreeMap::new();
+        let week_start = today - Duration::days(today.weekday().num_days_from_monday() as i64);
+
+        let mut interruptions_per_day: BTreeMap<NaiveDate, u32> = BTreeMap::new();
+
+        for record in records.iter().filter(|r| r.session_type == SessionType::Work) {
+            // Counted whatever became of the session
+            for interruption in &record.interruptions {
+                let date = interruption.at.date_naive();
+                *interruptions_per_day.entry(date).or_default() += 1;
+                if date == today {
+                    match interruption.kind {
+                        InterruptionKind::Internal => stats.internal_interruptions_today += 1,
+                        InterruptionKind::External => stats.external_interruptions_today += 1,
+                    }
+                }
+            }
+
+            stats.total_focus_minutes += record.focused_secs;
+            match record.outcome {
+                SessionOutcome::Completed => stats.completed += 1,
+                SessionOutcome::Skipped => stats.skipped += 1,
+                SessionOutcome::Voided => stats.voided += 1,
+                SessionOutcome::Reset => {}
+            }
+            if record.outcome != SessionOutcome::Completed {
+                continue;
+            }
+
+            let date = record.started_at.date_naive();
+            *per_day.entry(date).or_default() += 1;
+            if date == today {
+                stats.pomodoros_today += 1;
+            }
+            if date >= week_start && date <= today {
+                stats.pomodoros_this_week += 1;
+            }
+        }
+        stats.total_focus_minutes /= 60;
+
+        let days = stats.last_days.iter_mut().zip(stats.interruptions_last_days.iter_mut());
+        for (i, (count, interruptions)) in days.enumerate() {
+            let date = today - Duration::days((CHART_DAYS - 1 - i) as i64);
+            *count = per_day.get(&date).copied().unwrap_or(0);
+            *interruptions = interruptions_per_day.get(&date).copied().unwrap_or(0);
+        }
+
+        let mut streak = 0;
+        let mut previous: Option<NaiveDate> = None;
+        for &date in per_day.keys() {
+            streak = match previous {
+                Some(prev) if date - prev == Duration::days(1) => streak + 1,
+                _ => 1,
+            };
+            stats.longest_streak_days = stats.longest_streak_days.max(streak);
+            previous = Some(date);
+        }
+
+        stats
+    }
+
+    /// Share of finished-or-skipped work sessions that were completed, from 0 to 1.
+    pub fn completion_ratio(&self) -> Option<f32> {
+        let total = self.completed + self.skipped;
+        if total == 0 {
+            None
+        } else {
+            Some(self.completed as f32 / total as f32)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::history::Interruption;
+    use chrono::{Local, TimeZone};
+
+    fn work(day: u32, outcome: SessionOutcome, focused_min: u64) -> SessionRecord {
+        let started_at = Local.with_ymd_and_hms(2024, 5, day, 10, 0, 0).unwrap();
+        SessionRecord {
+            outcome,
+            focused_secs: focused_min * 60,
+            ended_at: started_at + Duration::minutes(focused_min as i64),
+            ..SessionRecord::pomodoro(started_at)
+        }
+    }
+
+    #[test]
+    fn summarises_work_sessions() {
+        use SessionOutcome::*;
+        // 2024-05-15 is a Wednesday
+        let today = NaiveDate::from_ymd_opt(2024, 5, 15).unwrap();
+        let mut records = vec![
+            work(1, Completed, 25),
+            work(2, Completed, 25),
+            work(3, Completed, 25),
+            work(12, Completed, 25),
+            work(13, Completed, 25),
+            work(14, Skipped, 10),
+            work(15, Completed, 25),
+            work(15, Completed, 25),
+            work(15, Reset, 5),
+            work(15, Voided, 12),
+        ];
+        let mut short_break = work(15, Completed, 5);
+        short_break.session_type = SessionType::ShortBreak;
+        records.push(short_break);
+
+        let stats = Stats::compute(&records, today);
+        assert_eq!(stats.pomodoros_today, 2);
+        assert_eq!(stats.pomodoros_this_week, 3);
+        assert_eq!(stats.total_focus_minutes, 7 * 25 + 10 + 5 + 12);
+        assert_eq!(stats.longest_streak_days, 3);
+        assert_eq!((stats.completed, stats.skipped, stats.voided), (7, 1, 1));
+        assert_eq!(stats.completion_ratio(), Some(7.0 / 8.0));
+        assert_eq!(stats.last_days, [0, 0, 0, 1, 1, 0, 2]);
+    }
+
+    #[test]
+    fn counts_interruptions_per_day() {
+        let interrupted = |day: u32, outcome, kinds: &[InterruptionKind]| {
+            let mut record = work(day, outcome, 25);
+            record.interruptions = kinds
+                .iter()
+                .map(|&kind| Interruption { kind, note: None, at: record.started_at, paused: false })
+                .collect();
+            record
+        };
+        let today = NaiveDate::from_ymd_opt(2024, 5, 15).unwrap();
+        let records = [
+            interrupted(14, SessionOutcome::Completed, &[InterruptionKind::External]),
+            interrupted(15, SessionOutcome::Completed, &[InterruptionKind::Internal, InterruptionKind::External]),
+            interrupted(15, SessionOutcome::Reset, &[InterruptionKind::Internal]),
+        ];
+
+        let stats = Stats::compute(&records, today);
+        assert_eq!((stats.internal_interruptions_today, stats.external_interruptions_today), (2, 1));
+        assert_eq!(stats.interruptions_last_days, [0, 0, 0, 0, 0, 1, 3]);
+    }
+}
@@ -0,0 +1,147 @@
+// Output for status bars such as waybar, polybar, i3blocks and i3bar. The
+// state comes from the running instance over the control socket; when nothing
+// is running the bar shows an idle block instead of disappearing.
+
+use clap::ValueEnum;
+use serde_json::json;
+use std::io::{self, Write};
+use std::thread;
+use std::time::Duration;
+
+use crate::control::{self, Request};
+use crate::timer::{SessionType, TimerStatus};
+
+const REFRESH: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum StatusFormat {
+    /// One line of text, e.g. for polybar or i3blocks
+    Plain,
+    /// The full timer status as JSON
+    Json,
+    /// A waybar custom module with `return-type: json`
+    Waybar,
+    /// Blocks in the i3bar protocol
+    I3bar,
+}
+
+/// CSS class for styling each kind of session in the bar.
+pub fn class_name(session_type: SessionType) -> &'static str {
+    match session_type {
+        SessionType::Work => "work",
+        SessionType::ShortBreak => "short-break",
+        SessionType::LongBreak => "long-break",
+    }
+}
+
+/// Asks the running instance for its state; `None` if nothing is running.
+pub fn current() -> Option<TimerStatus> {
+    control::send(&Request::Status).ok().and_then(|response| response.status)
+}
+
+/// Renders one update. `None` renders the idle state.
+pub fn render(format: StatusFormat, status: Option<&TimerStatus>) -> String {
+    let class = status.map_or("idle", |status| class_name(status.session_type));
+    let state = status.map_or("idle", TimerStatus::state);
+    let text = match status {
+        Some(status) => format!("{} {}/{}", status.time, status.session, status.cycle_length),
+        None => "--:--".to_string(),
+    };
+
+    match format {
+        StatusFormat::Plain => match status {
+            Some(status) if !status.is_running => format!("{} ({})", text, status.state()),
+            _ => text,
+        },
+        StatusFormat::Json => json!({
+            "status": status,
+            "class": class,
+            "state": state,
+        })
+        .to_string(),
+        StatusFormat::Waybar => {
+            let tooltip = match status {
+                Some(status) => format!(
+                    "{}: session {} of {}{}{}",
+                    status.session_type.name(),
+                    status.session,
+                    status.cycle_length,
+                    status.goal.map(|goal| format!("\nGoal: {}", goal.summary())).unwrap_or_default(),
+                    status.task.as_ref().map(|task| format!("\n{}", task)).unwrap_or_default(),
+                ),
+                None => "Pomodoro timer is not running".to_string(),
+            };
+            json!({
+                "text": text,
+                "alt": class,
+                "tooltip": tooltip,
+                "class": [class, state],
+                "percentage": status.map_or(0, |status| (status.progress * 100.0).round() as u32),
+            })
+            .to_string()
+        }
+        StatusFormat::I3bar => json!({
+            "name": "pomodoro",
+            "instance": class,
+            "full_text": text,
+            "urgent": status.is_some_and(|status| status.remaining_secs == 0),
+        })
+        .to_string(),
+    }
+}
+
+/// Prints an update every second until stdout is closed, e.g. by the bar exiting.
+pub fn follow(format: StatusFormat) -> io::Result<()> {
+    let mut stdout = io::stdout().lock();
+    if format == StatusFormat::I3bar {
+        // Protocol header, then an endless array of block lists
+        writeln!(stdout, "{}", json!({ "version": 1 }))?;
+        writeln!(stdout, "[")?;
+    }
+
+    loop {
+        let line = render(format, current().as_ref());
+        match format {
+            StatusFormat::I3bar => writeln!(stdout, "[{}],", line)?,
+            _ => writeln!(stdout, "{}", line)?,
+        }
+        stdout.flush()?;
+        thread::sleep(REFRESH);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status(session_type: SessionType, is_running: bool) -> TimerStatus {
+        TimerStatus {
+            progress: 0.1,
+            session: 2,
+            task: Some("Write report".to_string()),
+            ..TimerStatus::sample(session_type, 270, is_running)
+        }
+    }
+
+    #[test]
+    fn waybar_output_carries_session_classes() {
+        let output = render(StatusFormat::Waybar, Some(&status(SessionType::ShortBreak, false)));
+        let value: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(value["text"], "04:30 2/4");
+        assert_eq!(value["class"], json!(["short-break", "paused"]));
+        assert_eq!(value["percentage"], 10);
+
+        let idle: serde_json::Value = serde_json::from_str(&render(StatusFormat::Waybar, None)).unwrap();
+        assert_eq!(idle["class"], json!(["idle", "idle"]));
+    }
+
+    #[test]
+    fn plain_output_marks_paused_sessions() {
+        assert_eq!(render(StatusFormat::Plain, Some(&status(SessionType::Work, true))), "04:30 2/4");
+        assert_eq!(render(StatusFormat::Plain, Some(&status(SessionType::Work, false))), "04:30 2/4 (paused)");
+        assert_eq!(render(StatusFormat::Plain, None), "--:--");
+
+        let waiting = TimerStatus { waiting: true, ..status(SessionType::ShortBreak, false) };
+        assert_eq!(render(StatusFormat::Plain, Some(&waiting)), "04:30 2/4 (waiting)");
+    }
+}
@@ -0,0 +1,178 @@
+// Task list that pomodoros are attributed to, stored as JSON in the data
+// directory (e.g. ~/.local/share/rust-pomodoro/tasks.json on Linux).
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+use crate::history::{SessionOutcome, SessionRecord, SessionTask};
+use crate::paths;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Task {
+    pub id: u64,
+    pub name: String,
+    pub estimated_pomodoros: u32,
+    pub actual_pomodoros: u32,
+    pub completed: bool,
+    pub created_at: DateTime<Local>,
+    #[serde(default)]
+    pub completed_at: Option<DateTime<Local>>,
+}
+
+impl Task {
+    /// The reference stored with the sessions spent on this task.
+    pub fn tag(&self) -> SessionTask {
+        SessionTask {
+            id: self.id,
+            name: self.name.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TaskList {
+    next_id: u64,
+    tasks: Vec<Task>,
+    active: Option<u64>,
+}
+
+impl TaskList {
+    pub fn path() -> Option<PathBuf> {
+        paths::data_file("tasks.json")
+    }
+
+    pub fn load() -> Self {
+        let Some(path) = Self::path() else {
+            return Self::default();
+        };
+
+        match fs::read_to_string(&path) {
+            Ok(text) => serde_json::from_str(&text).unwrap_or_else(|e| {
+                eprintln!("Ignoring invalid task file {}: {}", path.display(), e);
+                Self::default()
+            }),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Self::default(),
+            Err(e) => {
+                eprintln!("Failed to read task file {}: {}", path.display(), e);
+                Self::default()
+            }
+        }
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let path = Self::path()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no data directory"))?;
+        let json = serde_json::to_string_pretty(self)?;
+        paths::write_atomic(&path, json.as_bytes())
+    }
+
+    pub fn tasks(&self) -> &[Task] {
+        &self.tasks
+    }
+
+    pub fn get(&self, id: u64) -> Option<&Task> {
+        self.tasks.iter().find(|task| task.id == id)
+    }
+
+    fn get_mut(&mut self, id: u64) -> Option<&mut Task> {
+        self.tasks.iter_mut().find(|task| task.id == id)
+    }
+
+    pub fn add(&mut self, name: &str, estimated_pomodoros: u32) -> u64 {
+        self.next_id += 1;
+        self.tasks.push(Task {
+            id: self.next_id,
+            name: name.trim().to_string(),
+            estimated_pomodoros,
+            actual_pomodoros: 0,
+            completed: false,
+            created_at: Local::now(),
+            completed_at: None,
+        });
+        self.next_id
+    }
+
+    pub fn rename(&mut self, id: u64, name: &str) {
+        if let Some(task) = self.get_mut(id) {
+            task.name = name.trim().to_string();
+        }
+    }
+
+    pub fn set_estimate(&mut self, id: u64, estimated_pomodoros: u32) {
+        if let Some(task) = self.get_mut(id) {
+            task.estimated_pomodoros = estimated_pomodoros;
+        }
+    }
+
+    /// Marks a task done (or reopens it). A completed task stops being active.
+    pub fn set_completed(&mut self, id: u64, completed: bool) {
+        if let Some(task) = self.get_mut(id) {
+            task.completed = completed;
+            task.completed_at = completed.then(Local::now);
+        }
+        if completed && self.active == Some(id) {
+            self.active = None;
+        }
+    }
+
+    pub fn active(&self) -> Option<&Task> {
+        self.active.and_then(|id| self.get(id))
+    }
+
+    pub fn set_active(&mut self, id: Option<u64>) {
+        self.active = id.filter(|&id| self.get(id).is_some_and(|task| !task.completed));
+    }
+
+    pub fn record_pomodoro(&mut self, id: u64) {
+        if let Some(task) = self.get_mut(id) {
+            task.actual_pomodoros += 1;
+        }
+    }
+
+    /// Credits completed work sessions to their tasks. Returns whether any
+    /// task changed and the list needs saving.
+    pub fn record_sessions(&mut self, records: &[SessionRecord]) -> bool {
+        let mut changed = false;
+        for record in records {
+            if let (SessionOutcome::Completed, Some(task)) = (record.outcome, &record.task) {
+                self.record_pomodoro(task.id);
+                changed = true;
+            }
+        }
+        changed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn completing_the_active_task_clears_it() {
+        let mut tasks = TaskList::default();
+        let write = tasks.add(" Write report ", 3);
+        let review = tasks.add("Review PR", 1);
+        assert_ne!(write, review);
+        assert_eq!(tasks.get(write).unwrap().name, "Write report");
+
+        tasks.set_active(Some(write));
+        tasks.record_pomodoro(write);
+        tasks.record_pomodoro(write);
+        assert_eq!(tasks.active().unwrap().actual_pomodoros, 2);
+
+        tasks.set_completed(write, true);
+        assert!(tasks.active().is_none());
+        assert!(tasks.get(write).unwrap().completed_at.is_some());
+
+        // Completed tasks cannot be picked until reopened
+        tasks.set_active(Some(write));
+        assert!(tasks.active().is_none());
+        tasks.set_completed(write, false);
+        tasks.set_active(Some(write));
+        assert_eq!(tasks.active().map(|t| t.id), Some(write));
+    }
+}
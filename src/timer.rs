@@ -1,69 +1,403 @@
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+use crate::clock::{Clock, SystemClock};
+use crate::goals::{GoalKind, GoalProgress, GoalTracker};
+use crate::history::{Interruption, InterruptionKind, SessionOutcome, SessionRecord, SessionTask};
+use crate::sequence::SessionSequence;
+
+// The least a session can be shortened to, so it always has a length
+const MIN_REMAINING: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum SessionType {
     Work,
     ShortBreak,
     LongBreak,
 }
 
+impl SessionType {
+    /// The name shown to the user, e.g. "Short Break".
+    pub fn name(self) -> &'static str {
+        match self {
+            SessionType::Work => "Work Session",
+            SessionType::ShortBreak => "Short Break",
+            SessionType::LongBreak => "Long Break",
+        }
+    }
+}
+
+/// The persistable part of a timer's state.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TimerSnapshot {
+    pub step: usize,
+    pub session_type: SessionType,
+    pub time_remaining: Duration,
+    pub total_duration: Duration,
+    pub is_running: bool,
+    #[serde(default)]
+    pub started_at: Option<DateTime<Local>>,
+    #[serde(default)]
+    pub task: Option<SessionTask>,
+    #[serde(default)]
+    pub waiting: bool,
+    #[serde(default)]
+    pub overtime: Option<Duration>,
+    #[serde(default)]
+    pub adjusted_secs: i64,
+    #[serde(default)]
+    pub interruptions: Vec<Interruption>,
+}
+
+impl TimerSnapshot {
+    /// Whether there is anything worth resuming, as opposed to a fresh timer.
+    pub fn is_in_progress(&self) -> bool {
+        self.is_running || self.step != 0 || self.time_remaining < self.total_duration
+    }
+}
+
+/// A point-in-time view of the timer for other processes and front ends.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TimerStatus {
+    pub session_type: SessionType,
+    pub time: String,
+    pub remaining_secs: u64,
+    pub progress: f32,
+    pub is_running: bool,
+    pub session: u32,
+    pub cycle_length: u32,
+    pub task: Option<String>,
+    /// The last session ended and this one waits for the user to start it.
+    #[serde(default)]
+    pub waiting: bool,
+    /// Set while a flow-mode work session runs past its end; `time` then
+    /// counts up, e.g. "+03:12".
+    #[serde(default)]
+    pub overtime_secs: Option<u64>,
+    /// Interruptions logged against the current session.
+    #[serde(default)]
+    pub interruptions: u32,
+    /// Progress towards the daily and weekly goals, if any are set.
+    #[serde(default)]
+    pub goal: Option<GoalProgress>,
+}
+
+impl TimerStatus {
+    /// "running", "overtime", "paused", or "waiting" for the user to start
+    /// the next session.
+    pub fn state(&self) -> &'static str {
+        if self.is_running && self.overtime_secs.is_some() {
+            "overtime"
+        } else if self.is_running {
+            "running"
+        } else if self.waiting {
+            "waiting"
+        } else {
+            "paused"
+        }
+    }
+}
+
+#[cfg(test)]
+impl TimerStatus {
+    /// The first session of a four-session cycle, for tests to adjust with
+    /// struct update syntax.
+    pub fn sample(session_type: SessionType, remaining_secs: u64, is_running: bool) -> Self {
+        Self {
+            session_type,
+            time: format!("{:02}:{:02}", remaining_secs / 60, remaining_secs % 60),
+            remaining_secs,
+            progress: 0.0,
+            is_running,
+            session: 1,
+            cycle_length: 4,
+            task: None,
+            waiting: false,
+            overtime_secs: None,
+            interruptions: 0,
+            goal: None,
+        }
+    }
+}
+
+/// Session changes, delivered to whoever called `PomodoroTimer::subscribe`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TimerEvent {
+    SessionStarted(SessionType),
+    SessionFinished(SessionRecord),
+}
+
 pub struct PomodoroTimer {
-    work_duration: Duration,
-    short_break_duration: Duration,
-    long_break_duration: Duration,
+    sequence: SessionSequence,
+    // A sequence set while a session ran; it takes over when that session ends
+    pending_sequence: Option<SessionSequence>,
+    step: usize,
     current_session: SessionType,
-    session_count: u32,
     time_remaining: Duration,
     is_running: bool,
     last_update: Option<Instant>,
     total_duration: Duration,
-    just_completed: bool,
+    just_completed: Option<SessionType>,
+    started_at: Option<DateTime<Local>>,
+    finished_sessions: Vec<SessionRecord>,
+    task: Option<SessionTask>,
+    auto_start_breaks: bool,
+    auto_start_work: bool,
+    waiting: bool,
+    flow_mode: bool,
+    overtime_to_break: bool,
+    // Time past the end of a work session in flow mode
+    overtime: Option<Duration>,
+    // Net seconds added to the current session by extend and shorten
+    adjusted_secs: i64,
+    interruptions: Vec<Interruption>,
+    goals: GoalTracker,
+    reached_goals: Vec<GoalKind>,
+    subscribers: Vec<Sender<TimerEvent>>,
+    clock: Arc<dyn Clock>,
 }
 
 impl PomodoroTimer {
     pub fn new() -> Self {
-        let work_duration = Duration::from_secs(25 * 60);
+        Self::with_clock(Arc::new(SystemClock))
+    }
+
+    pub fn with_clock(clock: Arc<dyn Clock>) -> Self {
+        let sequence = SessionSequence::default();
+        let first = sequence.step(0);
         Self {
-            work_duration,
-            short_break_duration: Duration::from_secs(5 * 60),
-            long_break_duration: Duration::from_secs(15 * 60),
-            current_session: SessionType::Work,
-            session_count: 1,
-            time_remaining: work_duration,
+            sequence,
+            pending_sequence: None,
+            step: 0,
+            current_session: first.session_type,
+            time_remaining: first.duration(),
             is_running: false,
             last_update: None,
-            total_duration: work_duration,
-            just_completed: false,
+            total_duration: first.duration(),
+            just_completed: None,
+            started_at: None,
+            finished_sessions: Vec::new(),
+            task: None,
+            auto_start_breaks: true,
+            auto_start_work: true,
+            waiting: false,
+            flow_mode: false,
+            overtime_to_break: false,
+            overtime: None,
+            adjusted_secs: 0,
+            interruptions: Vec::new(),
+            goals: GoalTracker::default(),
+            reached_goals: Vec::new(),
+            subscribers: Vec::new(),
+            clock,
         }
     }
 
-    pub fn update_durations(&mut self, work_min: u32, short_break_min: u32, long_break_min: u32) {
-        self.work_duration = Duration::from_secs(work_min as u64 * 60);
-        self.short_break_duration = Duration::from_secs(short_break_min as u64 * 60);
-        self.long_break_duration = Duration::from_secs(long_break_min as u64 * 60);
-        
-        if !self.is_running {
-            match self.current_session {
-                SessionType::Work => {
-                    self.time_remaining = self.work_duration;
-                    self.total_duration = self.work_duration;
-                }
-                SessionType::ShortBreak => {
-                    self.time_remaining = self.short_break_duration;
-                    self.total_duration = self.short_break_duration;
-                }
-                SessionType::LongBreak => {
-                    self.time_remaining = self.long_break_duration;
-                    self.total_duration = self.long_break_duration;
+    /// Replaces the session rotation. A running session keeps its remaining
+    /// time and the new sequence takes over once it ends.
+    pub fn set_sequence(&mut self, sequence: SessionSequence) {
+        if self.is_running {
+            self.pending_sequence = Some(sequence);
+            return;
+        }
+
+        self.pending_sequence = None;
+        self.sequence = sequence;
+        if self.step >= self.sequence.len() {
+            self.step = 0;
+        }
+        self.load_step();
+    }
+
+    /// The rotation, including one still waiting for the running session to end.
+    pub fn sequence(&self) -> &SessionSequence {
+        self.pending_sequence.as_ref().unwrap_or(&self.sequence)
+    }
+
+    /// Whether a session that runs out starts the next one by itself. When
+    /// it doesn't, the timer waits for `start` on the next session.
+    pub fn set_auto_start(&mut self, breaks: bool, work: bool) {
+        self.auto_start_breaks = breaks;
+        self.auto_start_work = work;
+    }
+
+    /// In flow mode a work session that reaches zero keeps going, counting
+    /// overtime, until it is skipped. `add_to_break` lengthens the following
+    /// break by the overtime.
+    pub fn set_flow_mode(&mut self, enabled: bool, add_to_break: bool) {
+        self.flow_mode = enabled;
+        self.overtime_to_break = add_to_break;
+    }
+
+    /// Replaces the goal tracker; finished sessions are counted towards it.
+    pub fn set_goals(&mut self, goals: GoalTracker) {
+        self.goals = goals;
+    }
+
+    pub fn goals(&self) -> &GoalTracker {
+        &self.goals
+    }
+
+    /// Recounts the goals over the whole history after sessions were added to
+    /// it elsewhere; goals that reaches are announced like any other.
+    pub fn recount_goals(&mut self, records: &[SessionRecord]) {
+        let today = self.clock.local_now().date_naive();
+        let reached = self.goals.recount(records, today);
+        self.reached_goals.extend(reached);
+    }
+
+    /// Goals reached since the last call, for the caller to celebrate.
+    pub fn take_reached_goals(&mut self) -> Vec<GoalKind> {
+        std::mem::take(&mut self.reached_goals)
+    }
+
+    fn load_step(&mut self) {
+        let step = self.sequence.step(self.step);
+        self.current_session = step.session_type;
+        self.time_remaining = step.duration();
+        self.total_duration = step.duration();
+        self.started_at = None;
+        self.overtime = None;
+        self.adjusted_secs = 0;
+        self.interruptions.clear();
+    }
+
+    /// Events happen as the timer is driven, so a subscriber only hears of a
+    /// session ending once some front end has called `update` or a getter.
+    pub fn subscribe(&mut self) -> Receiver<TimerEvent> {
+        let (sender, receiver) = mpsc::channel();
+        self.subscribers.push(sender);
+        receiver
+    }
+
+    fn emit(&mut self, event: TimerEvent) {
+        // Receivers that were dropped unsubscribe themselves
+        self.subscribers.retain(|subscriber| subscriber.send(event.clone()).is_ok());
+    }
+
+    // Queues a history record for the current session before it is replaced.
+    fn record_session(&mut self, outcome: SessionOutcome) {
+        self.record_session_with(outcome, None);
+    }
+
+    fn record_session_with(&mut self, outcome: SessionOutcome, void_reason: Option<String>) {
+        let ended_at = self.clock.local_now();
+        self.record_session_ended(outcome, void_reason, ended_at);
+    }
+
+    fn record_session_ended(&mut self, outcome: SessionOutcome, void_reason: Option<String>, ended_at: DateTime<Local>) {
+        let overtime = self.overtime.unwrap_or_default();
+        let record = SessionRecord {
+            session_type: self.current_session,
+            outcome,
+            planned_secs: self.total_duration.as_secs(),
+            focused_secs: (self.total_duration - self.time_remaining + overtime).as_secs(),
+            overtime_secs: overtime.as_secs(),
+            adjusted_secs: self.adjusted_secs,
+            started_at: self.started_at.unwrap_or(ended_at),
+            ended_at,
+            task: match self.current_session {
+                SessionType::Work => self.task.clone(),
+                _ => None,
+            },
+            interruptions: self.interruptions.clone(),
+            void_reason,
+        };
+        let reached = self.goals.record(&record);
+        self.reached_goals.extend(reached);
+        self.finished_sessions.push(record.clone());
+        self.emit(TimerEvent::SessionFinished(record));
+    }
+
+    /// Sets the task that work sessions are attributed to from now on.
+    pub fn set_task(&mut self, task: Option<SessionTask>) {
+        self.task = task;
+    }
+
+    /// Sessions that ended since the last call, oldest first, for the caller
+    /// to append to the history log.
+    pub fn take_finished_sessions(&mut self) -> Vec<SessionRecord> {
+        std::mem::take(&mut self.finished_sessions)
+    }
+
+    pub fn snapshot(&mut self) -> TimerSnapshot {
+        self.update();
+        TimerSnapshot {
+            step: self.step,
+            session_type: self.current_session,
+            time_remaining: self.time_remaining,
+            total_duration: self.total_duration,
+            is_running: self.is_running,
+            started_at: self.started_at,
+            task: self.task.clone(),
+            waiting: self.waiting,
+            overtime: self.overtime,
+            adjusted_secs: self.adjusted_secs,
+            interruptions: self.interruptions.clone(),
+        }
+    }
+
+    /// Restores a saved state. `away` is how long the app was closed: a running
+    /// session loses that much time, and one that would have ended meanwhile
+    /// gives way to the next session, paused so the user can start it.
+    pub fn restore(&mut self, snapshot: &TimerSnapshot, away: Duration) {
+        self.is_running = false;
+        self.last_update = None;
+        self.just_completed = None;
+        self.task = snapshot.task.clone();
+        self.waiting = snapshot.waiting && !snapshot.is_running;
+        if let Some(sequence) = self.pending_sequence.take() {
+            self.sequence = sequence;
+        }
+        self.step = snapshot.step % self.sequence.len();
+        self.load_step();
+
+        // The sequence may have been edited since; only trust the saved times
+        // if this step is still the same kind of session
+        if self.current_session == snapshot.session_type && !snapshot.total_duration.is_zero() {
+            self.total_duration = snapshot.total_duration;
+            self.time_remaining = snapshot.time_remaining.min(snapshot.total_duration);
+            self.started_at = snapshot.started_at;
+            self.overtime = snapshot.overtime;
+            self.adjusted_secs = snapshot.adjusted_secs;
+            self.interruptions = snapshot.interruptions.clone();
+        }
+
+        if snapshot.is_running {
+            if let Some(overtime) = &mut self.overtime {
+                // Overtime only ends when the user ends it
+                *overtime += away;
+                self.start();
+            } else if away >= self.time_remaining {
+                // It ended while the app was closed; record it as of then
+                let ended_at = self.clock.local_now()
+                    - chrono::Duration::from_std(away - self.time_remaining).unwrap_or(chrono::Duration::zero());
+                self.time_remaining = Duration::ZERO;
+                if self.started_at.is_some() {
+                    self.record_session_ended(SessionOutcome::Completed, None, ended_at);
                 }
+                self.step = (self.step + 1) % self.sequence.len();
+                self.load_step();
+                self.waiting = true;
+            } else {
+                self.time_remaining -= away;
+                self.start();
             }
         }
     }
 
     pub fn start(&mut self) {
         if !self.is_running {
+            self.waiting = false;
             self.is_running = true;
-            self.last_update = Some(Instant::now());
+            self.last_update = Some(self.clock.now());
+            if self.started_at.is_none() {
+                self.started_at = Some(self.clock.local_now());
+                self.emit(TimerEvent::SessionStarted(self.current_session));
+            }
         }
     }
 
@@ -72,36 +406,130 @@ impl PomodoroTimer {
         self.last_update = None;
     }
 
+    /// Logs an interruption against the current work session, pausing the
+    /// timer for it if `pause` is set. Returns false, logging nothing, when
+    /// no work session has started.
+    pub fn interrupt(&mut self, kind: InterruptionKind, note: Option<String>, pause: bool) -> bool {
+        self.update();
+        if self.current_session != SessionType::Work || self.started_at.is_none() {
+            return false;
+        }
+        let paused = pause && self.is_running;
+        self.interruptions.push(Interruption {
+            kind,
+            note: note.filter(|note| !note.trim().is_empty()),
+            at: self.clock.local_now(),
+            paused,
+        });
+        if paused {
+            self.pause();
+        }
+        true
+    }
+
+    /// Restarts the current session. In overtime the session already ran its
+    /// full length, so it is finished as completed instead.
     pub fn reset(&mut self) {
+        self.update();
+        if self.overtime.is_some() {
+            self.complete_session(SessionOutcome::Completed, false);
+            return;
+        }
+        if self.started_at.is_some() {
+            self.record_session(SessionOutcome::Reset);
+        }
         self.is_running = false;
+        self.waiting = false;
         self.last_update = None;
-        match self.current_session {
-            SessionType::Work => self.time_remaining = self.work_duration,
-            SessionType::ShortBreak => self.time_remaining = self.short_break_duration,
-            SessionType::LongBreak => self.time_remaining = self.long_break_duration,
+        self.load_step();
+    }
+
+    /// Abandons the current work session: it is recorded as voided, does not
+    /// count towards the cycle, and the same work session starts over,
+    /// stopped. Returns false when no work session has started. In overtime
+    /// the session is already complete, so it is finished as such instead.
+    pub fn void(&mut self, reason: Option<String>) -> bool {
+        self.update();
+        if self.current_session != SessionType::Work || self.started_at.is_none() {
+            return false;
+        }
+        if self.overtime.is_some() {
+            self.complete_session(SessionOutcome::Completed, false);
+            return true;
         }
+        let reason = reason.filter(|reason| !reason.trim().is_empty());
+        self.record_session_with(SessionOutcome::Voided, reason);
+        self.is_running = false;
+        self.waiting = false;
+        self.last_update = None;
+        self.load_step();
+        true
     }
 
     pub fn skip(&mut self) {
-        // Save the current running state before skipping
+        // A stopped timer stays stopped on the next session
         let was_running = self.is_running;
-        self.complete_session();
-        // If timer wasn't running before skip, stop it after transitioning
-        if !was_running {
-            self.is_running = false;
-            self.last_update = None;
+        self.update();
+        // Ending overtime finishes a session that already ran its full length
+        let outcome = if self.overtime.is_some() {
+            SessionOutcome::Completed
+        } else {
+            SessionOutcome::Skipped
+        };
+        self.complete_session(outcome, was_running);
+    }
+
+    /// Adds time to the current session, lengthening it rather than
+    /// restarting it, so progress so far is kept. Overtime is open-ended
+    /// already and is left alone.
+    pub fn extend(&mut self, by: Duration) {
+        self.update();
+        if self.overtime.is_some() {
+            return;
         }
+        self.time_remaining += by;
+        self.total_duration += by;
+        self.adjusted_secs += by.as_secs() as i64;
+    }
+
+    /// Takes time off the current session, always leaving at least
+    /// `MIN_REMAINING` of it so the session never drops to zero length.
+    pub fn shorten(&mut self, by: Duration) {
+        self.update();
+        if self.overtime.is_some() {
+            return;
+        }
+        let by = by.min(self.time_remaining.saturating_sub(MIN_REMAINING));
+        self.time_remaining -= by;
+        self.total_duration -= by;
+        self.adjusted_secs -= by.as_secs() as i64;
     }
 
     pub fn update(&mut self) {
         if self.is_running {
             if let Some(last_update) = self.last_update {
-                let now = Instant::now();
+                let now = self.clock.now();
                 let elapsed = now - last_update;
                 
-                if elapsed >= self.time_remaining {
+                if let Some(overtime) = &mut self.overtime {
+                    *overtime += elapsed;
+                } else if elapsed >= self.time_remaining
+                    && self.flow_mode
+                    && self.current_session == SessionType::Work
+                {
+                    // The bell rings but the session carries on
+                    self.overtime = Some(elapsed - self.time_remaining);
+                    self.time_remaining = Duration::ZERO;
+                    self.just_completed = Some(SessionType::Work);
+                } else if elapsed >= self.time_remaining {
                     self.time_remaining = Duration::ZERO;
-                    self.complete_session();
+                    let next = self.sequence();
+                    let auto_start = match next.step((self.step + 1) % next.len()).session_type {
+                        SessionType::Work => self.auto_start_work,
+                        _ => self.auto_start_breaks,
+                    };
+                    self.complete_session(SessionOutcome::Completed, auto_start);
+                    self.waiting = !auto_start;
                 } else {
                     self.time_remaining -= elapsed;
                 }
@@ -111,72 +539,551 @@ impl PomodoroTimer {
         }
     }
 
-    fn complete_session(&mut self) {
-        self.just_completed = true;
+    fn complete_session(&mut self, outcome: SessionOutcome, start_next: bool) {
+        // Overtime already announced the end of the session
+        if self.overtime.is_none() {
+            self.just_completed = Some(self.current_session);
+        }
+        self.record_session(outcome);
+        let overtime = self.overtime.unwrap_or_default();
 
-        match self.current_session {
-            SessionType::Work => {
-                if self.session_count % 4 == 0 {
-                    self.current_session = SessionType::LongBreak;
-                    self.time_remaining = self.long_break_duration;
-                    self.total_duration = self.long_break_duration;
-                } else {
-                    self.current_session = SessionType::ShortBreak;
-                    self.time_remaining = self.short_break_duration;
-                    self.total_duration = self.short_break_duration;
-                }
-            }
-            SessionType::ShortBreak | SessionType::LongBreak => {
-                self.current_session = SessionType::Work;
-                self.time_remaining = self.work_duration;
-                self.total_duration = self.work_duration;
-                if self.session_count % 4 == 0 {
-                    self.session_count = 1;
-                } else {
-                    self.session_count += 1;
-                }
-            }
+        if let Some(sequence) = self.pending_sequence.take() {
+            self.sequence = sequence;
+        }
+        self.step = (self.step + 1) % self.sequence.len();
+        self.load_step();
+        if self.overtime_to_break && self.current_session != SessionType::Work {
+            self.time_remaining += overtime;
+            self.total_duration += overtime;
+        }
+
+        self.is_running = false;
+        self.waiting = false;
+        self.last_update = None;
+        if start_next {
+            self.start();
         }
-        
-        // Automatically start the next session
-        self.is_running = true;
-        self.last_update = Some(Instant::now());
     }
 
     pub fn get_time_string(&mut self) -> String {
         self.update();
-        let total_seconds = self.time_remaining.as_secs();
+        let (sign, total_seconds) = match self.overtime {
+            Some(overtime) => ("+", overtime.as_secs()),
+            None => ("", self.time_remaining.as_secs()),
+        };
         let minutes = total_seconds / 60;
         let seconds = total_seconds % 60;
-        format!("{:02}:{:02}", minutes, seconds)
+        format!("{}{:02}:{:02}", sign, minutes, seconds)
     }
 
     pub fn get_progress(&mut self) -> f32 {
         self.update();
+        if self.total_duration.is_zero() {
+            return 0.0;
+        }
         let elapsed = self.total_duration - self.time_remaining;
         elapsed.as_secs_f32() / self.total_duration.as_secs_f32()
     }
 
+    pub fn status(&mut self) -> TimerStatus {
+        TimerStatus {
+            time: self.get_time_string(),
+            progress: self.get_progress(),
+            session_type: self.current_session,
+            remaining_secs: self.time_remaining.as_secs(),
+            is_running: self.is_running,
+            session: self.get_session_count(),
+            cycle_length: self.get_cycle_length(),
+            task: self.task.as_ref().map(|task| task.name.clone()),
+            waiting: self.waiting,
+            overtime_secs: self.overtime.map(|overtime| overtime.as_secs()),
+            interruptions: self.interruptions.len() as u32,
+            goal: self
+                .goals
+                .is_set()
+                .then(|| self.goals.progress(self.clock.local_now().date_naive())),
+        }
+    }
+
     pub fn is_running(&self) -> bool {
         self.is_running
     }
 
+    /// True between a session running out and the user starting the next
+    /// one, when auto-start is off for that transition.
+    pub fn is_waiting(&self) -> bool {
+        self.waiting
+    }
+
+    /// True while a flow-mode work session is running past its end.
+    pub fn is_in_overtime(&self) -> bool {
+        self.overtime.is_some()
+    }
+
     pub fn get_session_type(&self) -> SessionType {
         self.current_session
     }
 
+    /// Position of the current (or just finished) work session in the cycle.
     pub fn get_session_count(&self) -> u32 {
-        match self.current_session {
-            SessionType::Work => self.session_count,
-            _ => if self.session_count == 1 { 4 } else { self.session_count - 1 }
-        }
+        self.sequence.work_position(self.step)
+    }
+
+    /// Number of work sessions in one full cycle of the sequence.
+    pub fn get_cycle_length(&self) -> u32 {
+        self.sequence.cycle_length()
     }
 
-    pub fn just_completed(&self) -> bool {
+    /// The type of the session that just ended, until the flag is cleared.
+    pub fn just_completed(&self) -> Option<SessionType> {
         self.just_completed
     }
 
     pub fn clear_completed_flag(&mut self) {
-        self.just_completed = false;
+        self.just_completed = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::ManualClock;
+
+    const MINUTE: Duration = Duration::from_secs(60);
+
+    fn timer_with_clock() -> (PomodoroTimer, ManualClock) {
+        let clock = ManualClock::new();
+        let timer = PomodoroTimer::with_clock(Arc::new(clock.clone()));
+        (timer, clock)
+    }
+
+    // Runs the current session to the end in one clock step.
+    fn finish_session(timer: &mut PomodoroTimer, clock: &ManualClock) {
+        clock.advance(timer.time_remaining);
+        timer.update();
+    }
+
+    #[test]
+    fn counts_down_only_while_running() {
+        let (mut timer, clock) = timer_with_clock();
+        clock.advance(5 * MINUTE);
+        assert_eq!(timer.get_time_string(), "25:00");
+
+        timer.start();
+        clock.advance(MINUTE + Duration::from_secs(30));
+        assert_eq!(timer.get_time_string(), "23:30");
+        assert!((timer.get_progress() - 0.06).abs() < 1e-6);
+    }
+
+    #[test]
+    fn work_session_is_followed_by_short_break() {
+        let (mut timer, clock) = timer_with_clock();
+        timer.start();
+        finish_session(&mut timer, &clock);
+
+        assert_eq!(timer.just_completed(), Some(SessionType::Work));
+        assert_eq!(timer.get_session_type(), SessionType::ShortBreak);
+        assert_eq!(timer.get_time_string(), "05:00");
+        assert!(timer.is_running());
+        assert_eq!(timer.get_session_count(), 1);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn waits_for_confirmation_when_auto_start_is_off() {
+        let (mut timer, clock) = timer_with_clock();
+        timer.set_auto_start(false, true);
+        timer.start();
+        finish_session(&mut timer, &clock);
+        assert_eq!(timer.get_session_type(), SessionType::ShortBreak);
+        assert!(!timer.is_running());
+        assert!(timer.is_waiting());
+        assert!(timer.status().waiting);
+
+        clock.advance(10 * MINUTE);
+        assert_eq!(timer.get_time_string(), "05:00");
+        timer.start();
+        assert!(!timer.is_waiting());
+
+        // Breaks still lead straight back into work
+        finish_session(&mut timer, &clock);
+        assert_eq!(timer.get_session_type(), SessionType::Work);
+        assert!(timer.is_running());
+    }
+
+    #[test]
+    fn flow_mode_counts_overtime_and_adds_it_to_the_break() {
+        let (mut timer, clock) = timer_with_clock();
+        timer.set_flow_mode(true, true);
+        timer.start();
+        finish_session(&mut timer, &clock);
+        assert_eq!(timer.just_completed(), Some(SessionType::Work));
+        assert_eq!(timer.get_session_type(), SessionType::Work);
+        assert!(timer.is_in_overtime());
+
+        timer.clear_completed_flag();
+        clock.advance(3 * MINUTE + Duration::from_secs(12));
+        assert_eq!(timer.get_time_string(), "+03:12");
+        assert_eq!(timer.status().state(), "overtime");
+
+        timer.skip();
+        assert_eq!(timer.just_completed(), None);
+        assert_eq!(timer.get_session_type(), SessionType::ShortBreak);
+        assert_eq!(timer.get_time_string(), "08:12");
+
+        let records = timer.take_finished_sessions();
+        assert_eq!(records[0].outcome, SessionOutcome::Completed);
+        assert_eq!(records[0].overtime_secs, 192);
+        assert_eq!(records[0].focused_secs, 25 * 60 + 192);
+    }
+
+    #[test]
+    fn reset_or_void_in_overtime_completes_the_session() {
+        for void in [false, true] {
+            let (mut timer, clock) = timer_with_clock();
+            timer.set_flow_mode(true, false);
+            timer.start();
+            finish_session(&mut timer, &clock);
+            clock.advance(2 * MINUTE);
+
+            if void {
+                assert!(timer.void(Some("done anyway".to_string())));
+            } else {
+                timer.reset();
+            }
+            assert_eq!(timer.get_session_type(), SessionType::ShortBreak);
+            assert!(!timer.is_running());
+
+            let records = timer.take_finished_sessions();
+            assert_eq!(records.len(), 1);
+            assert_eq!(records[0].outcome, SessionOutcome::Completed);
+            assert_eq!(records[0].overtime_secs, 120);
+            assert_eq!(records[0].void_reason, None);
+        }
+    }
+
+    #[test]
+    fn interruptions_are_recorded_against_the_work_session() {
+        let (mut timer, clock) = timer_with_clock();
+        assert!(!timer.interrupt(InterruptionKind::External, None, true));
+
+        timer.start();
+        clock.advance(5 * MINUTE);
+        assert!(timer.interrupt(InterruptionKind::Internal, Some("email".to_string()), false));
+        assert!(timer.is_running());
+        assert!(timer.interrupt(InterruptionKind::External, Some(" ".to_string()), true));
+        assert!(!timer.is_running());
+        assert_eq!(timer.status().interruptions, 2);
+
+        timer.start();
+        finish_session(&mut timer, &clock);
+        assert_eq!(timer.status().interruptions, 0);
+        // Breaks can't be interrupted
+        assert!(!timer.interrupt(InterruptionKind::Internal, None, false));
+
+        let records = timer.take_finished_sessions();
+        let logged: Vec<_> = records[0]
+            .interruptions
+            .iter()
+            .map(|i| (i.kind, i.note.as_deref(), i.paused))
+            .collect();
+        assert_eq!(logged, [
+            (InterruptionKind::Internal, Some("email"), false),
+            (InterruptionKind::External, None, true),
+        ]);
+        assert_eq!(records[0].focused_secs, 25 * 60);
+    }
+
+    #[test]
+    fn void_records_the_session_without_advancing_the_cycle() {
+        let (mut timer, clock) = timer_with_clock();
+        assert!(!timer.void(None));
+
+        timer.start();
+        finish_session(&mut timer, &clock);
+        finish_session(&mut timer, &clock);
+        clock.advance(10 * MINUTE);
+        assert!(timer.void(Some("meeting ran over".to_string())));
+        assert!(!timer.is_running());
+        assert_eq!(timer.get_session_type(), SessionType::Work);
+        assert_eq!(timer.get_session_count(), 2);
+        assert_eq!(timer.get_time_string(), "25:00");
+
+        let voided = timer.take_finished_sessions().pop().unwrap();
+        assert_eq!(voided.outcome, SessionOutcome::Voided);
+        assert_eq!(voided.focused_secs, 10 * 60);
+        assert_eq!(voided.void_reason.as_deref(), Some("meeting ran over"));
+    }
+
+    #[test]
+    fn fourth_work_session_is_followed_by_long_break() {
+        let (mut timer, clock) = timer_with_clock();
+        timer.start();
+        for count in 1..=3 {
+            assert_eq!(timer.get_session_count(), count);
+            finish_session(&mut timer, &clock);
+            assert_eq!(timer.get_session_type(), SessionType::ShortBreak);
+            finish_session(&mut timer, &clock);
+            assert_eq!(timer.get_session_type(), SessionType::Work);
+        }
+
+        finish_session(&mut timer, &clock);
+        assert_eq!(timer.get_session_type(), SessionType::LongBreak);
+        assert_eq!(timer.get_time_string(), "15:00");
+        assert_eq!(timer.get_session_count(), 4);
+
+        finish_session(&mut timer, &clock);
+        assert_eq!(timer.get_session_type(), SessionType::Work);
+        assert_eq!(timer.get_session_count(), 1);
+    }
+
+    #[test]
+    fn skip_keeps_a_stopped_timer_stopped() {
+        let (mut timer, clock) = timer_with_clock();
+        timer.skip();
+        assert_eq!(timer.get_session_type(), SessionType::ShortBreak);
+        assert!(!timer.is_running());
+
+        clock.advance(MINUTE);
+        assert_eq!(timer.get_time_string(), "05:00");
+    }
+
+    #[test]
+    fn skip_keeps_a_running_timer_running() {
+        let (mut timer, clock) = timer_with_clock();
+        timer.start();
+        clock.advance(10 * MINUTE);
+        timer.skip();
+        assert_eq!(timer.get_session_type(), SessionType::ShortBreak);
+        assert!(timer.is_running());
+
+        clock.advance(MINUTE);
+        assert_eq!(timer.get_time_string(), "04:00");
+    }
+
+    #[test]
+    fn reset_restores_full_session_and_stops() {
+        let (mut timer, clock) = timer_with_clock();
+        timer.start();
+        clock.advance(7 * MINUTE);
+        timer.reset();
+        assert!(!timer.is_running());
+        assert_eq!(timer.get_time_string(), "25:00");
+        assert_eq!(timer.get_session_type(), SessionType::Work);
+    }
+
+    #[test]
+    fn extend_lengthens_the_current_session() {
+        let (mut timer, clock) = timer_with_clock();
+        timer.start();
+        clock.advance(20 * MINUTE);
+        timer.extend(5 * MINUTE);
+        assert_eq!(timer.get_time_string(), "10:00");
+        assert!((timer.get_progress() - 2.0 / 3.0).abs() < 1e-6);
+
+        finish_session(&mut timer, &clock);
+        let records = timer.take_finished_sessions();
+        assert_eq!(records[0].planned_secs, 30 * 60);
+        assert_eq!(records[0].adjusted_secs, 5 * 60);
+    }
+
+    #[test]
+    fn shorten_never_goes_below_the_time_already_spent() {
+        let (mut timer, clock) = timer_with_clock();
+        timer.start();
+        clock.advance(10 * MINUTE);
+        timer.shorten(5 * MINUTE);
+        assert_eq!(timer.get_time_string(), "10:00");
+        assert!((timer.get_progress() - 0.5).abs() < 1e-6);
+
+        timer.shorten(30 * MINUTE);
+        assert_eq!(timer.get_time_string(), "01:00");
+        clock.advance(MINUTE);
+        timer.update();
+        assert_eq!(timer.get_session_type(), SessionType::ShortBreak);
+
+        let records = timer.take_finished_sessions();
+        assert_eq!(records[0].planned_secs, 11 * 60);
+        assert_eq!(records[0].focused_secs, 11 * 60);
+        assert_eq!(records[0].adjusted_secs, -14 * 60);
+    }
+
+    #[test]
+    fn shortening_a_fresh_session_past_zero_keeps_a_minute() {
+        let (mut timer, _clock) = timer_with_clock();
+        for _ in 0..10 {
+            timer.shorten(5 * MINUTE);
+        }
+        assert_eq!(timer.get_time_string(), "01:00");
+        assert_eq!(timer.get_progress(), 0.0);
+
+        let status = serde_json::to_string(&timer.status()).unwrap();
+        assert!(serde_json::from_str::<TimerStatus>(&status).is_ok());
+    }
+
+    #[test]
+    fn pause_freezes_remaining_time() {
+        let (mut timer, clock) = timer_with_clock();
+        timer.start();
+        clock.advance(10 * MINUTE);
+        timer.update();
+        timer.pause();
+
+        clock.advance(30 * MINUTE);
+        assert_eq!(timer.get_time_string(), "15:00");
+        assert_eq!(timer.get_session_type(), SessionType::Work);
+
+        timer.start();
+        clock.advance(5 * MINUTE);
+        assert_eq!(timer.get_time_string(), "10:00");
+    }
+
+    #[test]
+    fn follows_a_custom_sequence() {
+        let (mut timer, clock) = timer_with_clock();
+        timer.set_sequence(SessionSequence::parse("W25 S5 W50 L20").unwrap());
+        assert_eq!(timer.get_cycle_length(), 2);
+        timer.start();
+
+        finish_session(&mut timer, &clock);
+        finish_session(&mut timer, &clock);
+        assert_eq!(timer.get_session_type(), SessionType::Work);
+        assert_eq!(timer.get_time_string(), "50:00");
+        assert_eq!(timer.get_session_count(), 2);
+
+        finish_session(&mut timer, &clock);
+        assert_eq!(timer.get_session_type(), SessionType::LongBreak);
+        assert_eq!(timer.get_time_string(), "20:00");
+
+        finish_session(&mut timer, &clock);
+        assert_eq!(timer.get_session_type(), SessionType::Work);
+        assert_eq!(timer.get_session_count(), 1);
+    }
+
+    #[test]
+    fn sequence_changed_mid_session_takes_over_when_it_ends() {
+        let (mut timer, clock) = timer_with_clock();
+        timer.start();
+        for _ in 0..4 {
+            finish_session(&mut timer, &clock);
+        }
+        clock.advance(10 * MINUTE);
+
+        timer.set_sequence(SessionSequence::parse("W50 L20").unwrap());
+        assert_eq!(timer.sequence().to_string(), "W50 L20");
+        assert_eq!(timer.get_session_type(), SessionType::Work);
+        assert_eq!(timer.get_time_string(), "15:00");
+        assert_eq!(timer.get_session_count(), 3);
+        assert!(timer.is_running());
+
+        finish_session(&mut timer, &clock);
+        assert_eq!(timer.get_session_type(), SessionType::LongBreak);
+        assert_eq!(timer.get_time_string(), "20:00");
+        finish_session(&mut timer, &clock);
+        assert_eq!(timer.get_session_type(), SessionType::Work);
+        assert_eq!(timer.get_time_string(), "50:00");
+        assert_eq!(timer.get_session_count(), 1);
+    }
+
+    #[test]
+    fn restore_deducts_time_spent_closed() {
+        let (mut timer, clock) = timer_with_clock();
+        timer.start();
+        clock.advance(10 * MINUTE);
+        let snapshot = timer.snapshot();
+        assert!(snapshot.is_in_progress());
+
+        let (mut resumed, _clock) = timer_with_clock();
+        resumed.restore(&snapshot, 3 * MINUTE);
+        assert!(resumed.is_running());
+        assert_eq!(resumed.get_time_string(), "12:00");
+    }
+
+    #[test]
+    fn restore_moves_past_a_session_that_ended_while_closed() {
+        let (mut timer, clock) = timer_with_clock();
+        timer.start();
+        clock.advance(20 * MINUTE);
+        let snapshot = timer.snapshot();
+
+        let (mut resumed, resumed_clock) = timer_with_clock();
+        resumed.restore(&snapshot, 8 * MINUTE);
+        assert!(!resumed.is_running());
+        assert_eq!(resumed.just_completed(), None);
+        assert_eq!(resumed.get_session_type(), SessionType::ShortBreak);
+        assert_eq!(resumed.get_time_string(), "05:00");
+
+        // The pomodoro still counts, as of when it ran out
+        let records = resumed.take_finished_sessions();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].session_type, SessionType::Work);
+        assert_eq!(records[0].outcome, SessionOutcome::Completed);
+        assert_eq!(records[0].focused_secs, 25 * 60);
+        assert_eq!(records[0].started_at, snapshot.started_at.unwrap());
+        assert_eq!(records[0].ended_at, resumed_clock.local_now() - chrono::Duration::minutes(3));
+    }
+
+    #[test]
+    fn finished_sessions_are_recorded_with_their_outcome() {
+        let (mut timer, clock) = timer_with_clock();
+        timer.start();
+        let work_started = clock.local_now();
+        finish_session(&mut timer, &clock);
+
+        clock.advance(2 * MINUTE);
+        timer.skip();
+        clock.advance(10 * MINUTE);
+        timer.reset();
+        // Resetting a session that never started leaves nothing to record
+        timer.reset();
+
+        let records = timer.take_finished_sessions();
+        let summary: Vec<_> = records
+            .iter()
+            .map(|r| (r.session_type, r.outcome, r.planned_secs / 60, r.focused_secs / 60))
+            .collect();
+        assert_eq!(summary, vec![
+            (SessionType::Work, SessionOutcome::Completed, 25, 25),
+            (SessionType::ShortBreak, SessionOutcome::Skipped, 5, 2),
+            (SessionType::Work, SessionOutcome::Reset, 25, 10),
+        ]);
+        assert_eq!(records[0].started_at, work_started);
+        assert_eq!(records[0].ended_at, work_started + chrono::Duration::minutes(25));
+        assert!(timer.take_finished_sessions().is_empty());
+    }
+
+    #[test]
+    fn only_work_sessions_are_attributed_to_the_task() {
+        let (mut timer, clock) = timer_with_clock();
+        timer.set_task(Some(SessionTask { id: 7, name: "Write report".to_string() }));
+        timer.start();
+        finish_session(&mut timer, &clock);
+        finish_session(&mut timer, &clock);
+
+        let tasks: Vec<_> = timer
+            .take_finished_sessions()
+            .into_iter()
+            .map(|r| r.task.map(|t| t.id))
+            .collect();
+        assert_eq!(tasks, vec![Some(7), None]);
+    }
+
+    #[test]
+    fn subscribers_hear_sessions_start_and_finish() {
+        let (mut timer, clock) = timer_with_clock();
+        let events = timer.subscribe();
+        timer.start();
+        finish_session(&mut timer, &clock);
+        timer.pause();
+        timer.skip();
+
+        let events: Vec<_> = events
+            .try_iter()
+            .map(|event| match event {
+                TimerEvent::SessionStarted(session_type) => format!("started {:?}", session_type),
+                TimerEvent::SessionFinished(record) => format!("{:?} {:?}", record.outcome, record.session_type),
+            })
+            .collect();
+        // A skip while paused leaves the next session unstarted
+        assert_eq!(events, ["started Work", "Completed Work", "started ShortBreak", "Skipped ShortBreak"]);
+    }
+}
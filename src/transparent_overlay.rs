@@ -57,6 +57,7 @@ impl Tomato {
 pub struct TransparentOverlay {
     tomatoes: Vec<Tomato>,
     texture: Option<egui::TextureHandle>,
+    caption: Option<String>,
     start_time: Instant,
     elapsed_time: f32,
     spawn_timer: f32,
@@ -67,10 +68,11 @@ pub struct TransparentOverlay {
 }
 
 impl TransparentOverlay {
-    pub fn new() -> Self {
+    pub fn new(caption: Option<String>) -> Self {
         Self {
             tomatoes: Vec::new(),
             texture: None,
+            caption,
             start_time: Instant::now(),
             elapsed_time: 0.0,
             spawn_timer: 0.0,
@@ -81,7 +83,7 @@ impl TransparentOverlay {
         }
     }
 
-    pub fn show() {
+    pub fn show(caption: Option<String>) {
         #[cfg(debug_assertions)]
         println!("Starting tomato overlay animation...");
         
@@ -114,7 +116,7 @@ impl TransparentOverlay {
                 // Note: Windows-specific transparency will be applied in the first update() call
                 // when we can get the window handle
                 
-                Box::new(TransparentOverlay::new())
+                Box::new(TransparentOverlay::new(caption))
             }),
         ) {
             #[cfg(debug_assertions)]
@@ -230,6 +232,16 @@ impl TransparentOverlay {
             }
         }
 
+        if let Some(caption) = &self.caption {
+            ui.painter().text(
+                ctx.screen_rect().center(),
+                egui::Align2::CENTER_CENTER,
+                caption,
+                egui::FontId::proportional(40.0),
+                Color32::WHITE,
+            );
+        }
+
         // Close after animation duration
         if self.elapsed_time > ANIMATION_DURATION.as_secs_f32() {
             self.active = false;
@@ -1,16 +1,303 @@
-// System tray integration placeholder
-// Full implementation would use tray-icon crate
+// System tray icon showing the remaining minutes, coloured by session type,
+// with a menu to control the timer. On Linux it is a StatusNotifierItem served
+// over D-Bus (KDE, waybar, GNOME with the AppIndicator extension, ...); other
+// platforms have no tray yet and `TrayManager::start` returns `None`.
+#![cfg_attr(not(target_os = "linux"), allow(dead_code))]
 
-#[allow(dead_code)]
+use std::sync::{Arc, Mutex};
+
+use crate::timer::{PomodoroTimer, SessionType, TimerStatus};
+
+#[cfg(target_os = "linux")]
+use std::sync::atomic::{AtomicBool, Ordering};
+#[cfg(target_os = "linux")]
+use std::time::Duration;
+
+const ICON_SIZE: usize = 32;
+#[cfg(target_os = "linux")]
+const REFRESH: Duration = Duration::from_secs(1);
+
+// 3x5 digits, one string per row, scaled up when drawn
+const DIGITS: [[&str; 5]; 10] = [
+    ["###", "#.#", "#.#", "#.#", "###"],
+    [".#.", "##.", ".#.", ".#.", "###"],
+    ["###", "..#", "###", "#..", "###"],
+    ["###", "..#", "###", "..#", "###"],
+    ["#.#", "#.#", "###", "..#", "..#"],
+    ["###", "#..", "###", "..#", "###"],
+    ["###", "#..", "###", "#.#", "###"],
+    ["###", "..#", "..#", "..#", "..#"],
+    ["###", "#.#", "###", "#.#", "###"],
+    ["###", "#.#", "###", "..#", "###"],
+];
+const DIGIT_SCALE: usize = 3;
+
+/// What the window has to do in response to the tray.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrayEvent {
+    /// The timer was started, paused, skipped or reset from the menu
+    TimerChanged,
+    ShowWindow,
+    Quit,
+}
+
+#[cfg(target_os = "linux")]
+pub struct TrayManager {
+    handle: ksni::blocking::Handle<PomodoroTray>,
+    running: Arc<AtomicBool>,
+}
+
+#[cfg(not(target_os = "linux"))]
 pub struct TrayManager;
 
-#[allow(dead_code)]
 impl TrayManager {
-    pub fn new() -> Self {
-        Self
+    /// Shows the icon, or returns `None` if there is no tray to show it in.
+    #[cfg(target_os = "linux")]
+    pub fn start(
+        timer: Arc<Mutex<PomodoroTimer>>,
+        on_event: impl Fn(TrayEvent) + Send + Sync + 'static,
+    ) -> Option<Self> {
+        use ksni::blocking::TrayMethods;
+
+        let status = timer.lock().unwrap().status();
+        let tray = PomodoroTray {
+            timer: timer.clone(),
+            status,
+            on_event: Arc::new(on_event),
+        };
+        let handle = match tray.spawn() {
+            Ok(handle) => handle,
+            Err(e) => {
+                eprintln!("Tray icon unavailable: {}", e);
+                return None;
+            }
+        };
+
+        // Keep the icon and tooltip in step with the countdown; ksni only
+        // signals the properties that actually changed
+        let running = Arc::new(AtomicBool::new(true));
+        {
+            let handle = handle.clone();
+            let running = running.clone();
+            std::thread::spawn(move || {
+                while running.load(Ordering::SeqCst) && !handle.is_closed() {
+                    let status = timer.lock().unwrap().status();
+                    handle.update(|tray| tray.status = status);
+                    std::thread::sleep(REFRESH);
+                }
+            });
+        }
+
+        Some(Self { handle, running })
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    pub fn start(
+        _timer: Arc<Mutex<PomodoroTimer>>,
+        _on_event: impl Fn(TrayEvent) + Send + Sync + 'static,
+    ) -> Option<Self> {
+        None
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl Drop for TrayManager {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::SeqCst);
+        // Not waited for; the D-Bus connection closes with the process
+        drop(self.handle.shutdown());
+    }
+}
+
+#[cfg(target_os = "linux")]
+struct PomodoroTray {
+    timer: Arc<Mutex<PomodoroTimer>>,
+    status: TimerStatus,
+    on_event: Arc<dyn Fn(TrayEvent) + Send + Sync>,
+}
+
+#[cfg(target_os = "linux")]
+impl PomodoroTray {
+    fn control(&mut self, action: fn(&mut PomodoroTimer)) {
+        let mut timer = self.timer.lock().unwrap();
+        action(&mut timer);
+        self.status = timer.status();
+        drop(timer);
+        (self.on_event)(TrayEvent::TimerChanged);
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl ksni::Tray for PomodoroTray {
+    fn id(&self) -> String {
+        "rust-pomodoro".to_string()
+    }
+
+    fn title(&self) -> String {
+        "Pomodoro Timer".to_string()
     }
 
-    pub fn init(&self) {
-        // TODO: Implement system tray
+    fn icon_pixmap(&self) -> Vec<ksni::Icon> {
+        vec![ksni::Icon {
+            width: ICON_SIZE as i32,
+            height: ICON_SIZE as i32,
+            data: render_icon(&self.status),
+        }]
+    }
+
+    fn tool_tip(&self) -> ksni::ToolTip {
+        let state = if self.status.is_running {
+            String::new()
+        } else {
+            format!(" ({})", self.status.state())
+        };
+        let mut description = format!("Session {} of {}", self.status.session, self.status.cycle_length);
+        if let Some(goal) = &self.status.goal {
+            description.push_str(&format!("\nGoal: {}", goal.summary()));
+        }
+        if let Some(task) = &self.status.task {
+            description.push_str(&format!("\n{}", task));
+        }
+        ksni::ToolTip {
+            title: format!("{} {}{}", self.status.session_type.name(), self.status.time, state),
+            description,
+            ..Default::default()
+        }
+    }
+
+    fn activate(&mut self, _x: i32, _y: i32) {
+        (self.on_event)(TrayEvent::ShowWindow);
+    }
+
+    fn menu(&self) -> Vec<ksni::MenuItem<Self>> {
+        use ksni::menu::StandardItem;
+
+        let toggle = if self.status.is_running {
+            StandardItem {
+                label: "Pause".to_string(),
+                activate: Box::new(|tray: &mut Self| tray.control(PomodoroTimer::pause)),
+                ..Default::default()
+            }
+        } else {
+            StandardItem {
+                label: "Start".to_string(),
+                activate: Box::new(|tray: &mut Self| tray.control(PomodoroTimer::start)),
+                ..Default::default()
+            }
+        };
+
+        vec![
+            toggle.into(),
+            StandardItem {
+                label: "Skip".to_string(),
+                activate: Box::new(|tray: &mut Self| tray.control(PomodoroTimer::skip)),
+                ..Default::default()
+            }
+            .into(),
+            StandardItem {
+                label: "Reset".to_string(),
+                activate: Box::new(|tray: &mut Self| tray.control(PomodoroTimer::reset)),
+                ..Default::default()
+            }
+            .into(),
+            ksni::MenuItem::Separator,
+            StandardItem {
+                label: "Show window".to_string(),
+                activate: Box::new(|tray: &mut Self| (tray.on_event)(TrayEvent::ShowWindow)),
+                ..Default::default()
+            }
+            .into(),
+            StandardItem {
+                label: "Quit".to_string(),
+                activate: Box::new(|tray: &mut Self| (tray.on_event)(TrayEvent::Quit)),
+                ..Default::default()
+            }
+            .into(),
+        ]
+    }
+}
+
+fn session_rgb(session_type: SessionType) -> [u8; 3] {
+    match session_type {
+        SessionType::Work => [255, 99, 71],
+        SessionType::ShortBreak => [76, 175, 80],
+        SessionType::LongBreak => [66, 133, 244],
+    }
+}
+
+// Whole minutes left, rounded up so "1" shows until the session ends
+fn icon_minutes(status: &TimerStatus) -> u64 {
+    status.remaining_secs.div_ceil(60).min(99)
+}
+
+/// Draws the icon as ARGB32: a disc in the session colour (greyed out while
+/// paused) with the remaining minutes on top.
+fn render_icon(status: &TimerStatus) -> Vec<u8> {
+    let [r, g, b] = if status.is_running {
+        session_rgb(status.session_type)
+    } else {
+        [140, 140, 140]
+    };
+
+    let mut data = vec![0u8; ICON_SIZE * ICON_SIZE * 4];
+    let center = (ICON_SIZE as f32 - 1.0) / 2.0;
+    for y in 0..ICON_SIZE {
+        for x in 0..ICON_SIZE {
+            let distance = ((x as f32 - center).powi(2) + (y as f32 - center).powi(2)).sqrt();
+            if distance <= ICON_SIZE as f32 / 2.0 {
+                let i = (y * ICON_SIZE + x) * 4;
+                data[i..i + 4].copy_from_slice(&[255, r, g, b]);
+            }
+        }
+    }
+
+    let text = icon_minutes(status).to_string();
+    let digit_width = 3 * DIGIT_SCALE;
+    let text_width = text.len() * digit_width + (text.len() - 1) * DIGIT_SCALE / 2;
+    let left = (ICON_SIZE - text_width) / 2;
+    let top = (ICON_SIZE - 5 * DIGIT_SCALE) / 2;
+    for (n, digit) in text.bytes().enumerate() {
+        let glyph = DIGITS[(digit - b'0') as usize];
+        let x0 = left + n * (digit_width + DIGIT_SCALE / 2);
+        for (row, line) in glyph.iter().enumerate() {
+            for (col, cell) in line.bytes().enumerate() {
+                if cell != b'#' {
+                    continue;
+                }
+                for dy in 0..DIGIT_SCALE {
+                    for dx in 0..DIGIT_SCALE {
+                        let x = x0 + col * DIGIT_SCALE + dx;
+                        let y = top + row * DIGIT_SCALE + dy;
+                        let i = (y * ICON_SIZE + x) * 4;
+                        data[i..i + 4].copy_from_slice(&[255, 255, 255, 255]);
+                    }
+                }
+            }
+        }
+    }
+    data
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn icon_is_coloured_by_session_and_shows_minutes() {
+        assert_eq!(icon_minutes(&TimerStatus::sample(SessionType::Work, 24 * 60 + 1, true)), 25);
+        assert_eq!(icon_minutes(&TimerStatus::sample(SessionType::Work, 59, true)), 1);
+
+        // The top-left of the disc, clear of the digits
+        let pixel = |data: &[u8]| {
+            let i = (8 * ICON_SIZE + 8) * 4;
+            data[i..i + 4].to_vec()
+        };
+        let work = render_icon(&TimerStatus::sample(SessionType::Work, 300, true));
+        assert_eq!(work.len(), ICON_SIZE * ICON_SIZE * 4);
+        assert_eq!(pixel(&work), [255, 255, 99, 71]);
+        let paused = render_icon(&TimerStatus::sample(SessionType::LongBreak, 300, false));
+        assert_eq!(pixel(&paused), [255, 140, 140, 140]);
+        // Corners stay transparent
+        assert_eq!(work[0], 0);
     }
-}
\ No newline at end of file
+}
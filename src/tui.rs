@@ -0,0 +1,504 @@
+// Full-screen terminal front end. Drives the same PomodoroTimer as the window:
+// sessions are recorded and credited to tasks, notifications go through the
+// same path, and the control socket is served so `ctl` and status bars work.
+
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Alignment, Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, Gauge, List, ListItem, ListState, Paragraph};
+use ratatui::{DefaultTerminal, Frame};
+use std::io;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::config::Config;
+use crate::goals;
+#[cfg(unix)]
+use crate::control;
+#[cfg(target_os = "linux")]
+use crate::dbus;
+use crate::headless::record_finished_sessions;
+use crate::history::{InterruptionKind, SessionHistory};
+use crate::notifications;
+use crate::resume::SavedSession;
+use crate::sound::SoundPlayer;
+use crate::tasks::TaskList;
+use crate::timer::{PomodoroTimer, SessionType, TimerStatus};
+use crate::SNAPSHOT_INTERVAL;
+
+const TICK: Duration = Duration::from_millis(200);
+const TOMATO: Color = Color::Rgb(255, 99, 71);
+const DIGIT_ROWS: usize = 5;
+
+// Five-row glyphs for the countdown, in the order of `GLYPH_CHARS`
+const GLYPH_CHARS: &str = "0123456789:+";
+const GLYPHS: [[&str; DIGIT_ROWS]; 12] = [
+    ["█████", "█   █", "█   █", "█   █", "█████"],
+    ["  ██ ", "   █ ", "   █ ", "   █ ", "  ███"],
+    ["█████", "    █", "█████", "█    ", "█████"],
+    ["█████", "    █", "█████", "    █", "█████"],
+    ["█   █", "█   █", "█████", "    █", "    █"],
+    ["█████", "█    ", "█████", "    █", "█████"],
+    ["█████", "█    ", "█████", "█   █", "█████"],
+    ["█████", "    █", "    █", "    █", "    █"],
+    ["█████", "█   █", "█████", "█   █", "█████"],
+    ["█████", "█   █", "█████", "    █", "█████"],
+    ["   ", " █ ", "   ", " █ ", "   "],
+    ["   ", " █ ", "███", " █ ", "   "],
+];
+
+pub fn run(config: Config) -> io::Result<()> {
+    let mut app = TuiApp::new(config);
+
+    // Started before the terminal switches screens so errors stay readable
+    #[cfg(unix)]
+    let _control = control::ControlServer::start(app.timer.clone(), |_| {})
+        .map_err(|e| eprintln!("Control socket unavailable: {}", e))
+        .ok();
+    #[cfg(target_os = "linux")]
+    let _dbus = dbus::DbusService::start(app.timer.clone(), || {})
+        .map_err(|e| eprintln!("D-Bus service unavailable: {}", e))
+        .ok();
+
+    let mut terminal = ratatui::init();
+    let result = app.run(&mut terminal);
+    ratatui::restore();
+
+    app.shutdown();
+    result
+}
+
+struct TuiApp {
+    timer: Arc<Mutex<PomodoroTimer>>,
+    config: Config,
+    tasks: TaskList,
+    history: Option<SessionHistory>,
+    sound: SoundPlayer,
+    pending_resume: Option<SavedSession>,
+    last_snapshot: Instant,
+    // Open task picker; row 0 is "No task", then the open tasks in order
+    picker: Option<ListState>,
+    message: Option<String>,
+    quit: bool,
+}
+
+impl TuiApp {
+    fn new(config: Config) -> Self {
+        let tasks = TaskList::load();
+        let mut timer = PomodoroTimer::new();
+        timer.set_sequence(config.sequence());
+        timer.set_auto_start(config.auto_start_breaks, config.auto_start_work);
+        timer.set_flow_mode(config.flow_mode, config.overtime_to_break);
+        let history = SessionHistory::open_default();
+        timer.set_goals(goals::tracker(&config, history.as_ref(), chrono::Local::now().date_naive()));
+        timer.set_task(tasks.active().map(|task| task.tag()));
+
+        Self {
+            timer: Arc::new(Mutex::new(timer)),
+            config,
+            tasks,
+            history,
+            sound: SoundPlayer::new(),
+            pending_resume: SavedSession::load(),
+            last_snapshot: Instant::now(),
+            picker: None,
+            message: None,
+            quit: false,
+        }
+    }
+
+    fn run(&mut self, terminal: &mut DefaultTerminal) -> io::Result<()> {
+        while !self.quit {
+            self.tick();
+            let status = self.timer.lock().unwrap().status();
+            terminal.draw(|frame| self.draw(frame, &status))?;
+
+            if event::poll(TICK)? {
+                if let Event::Key(key) = event::read()? {
+                    if key.kind == KeyEventKind::Press {
+                        self.handle_key(key);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn tick(&mut self) {
+        // As in the window, a pending resume prompt keeps the saved state intact
+        if self.pending_resume.is_none() && self.last_snapshot.elapsed() >= SNAPSHOT_INTERVAL {
+            self.save_snapshot();
+        }
+
+        let mut timer = self.timer.lock().unwrap();
+        timer.update();
+        if let Some(finished) = timer.just_completed() {
+            timer.clear_completed_flag();
+            self.message = Some(format!(
+                "{} finished at {}",
+                finished.name(),
+                chrono::Local::now().format("%H:%M")
+            ));
+            if timer.is_in_overtime() {
+                self.message = Some(format!(
+                    "{} time is up at {}; counting overtime, press s to take the break",
+                    finished.name(),
+                    chrono::Local::now().format("%H:%M")
+                ));
+            } else if timer.is_waiting() {
+                self.message = Some(format!(
+                    "{} finished at {}; press space to start the {}",
+                    finished.name(),
+                    chrono::Local::now().format("%H:%M"),
+                    timer.get_session_type().name().to_lowercase()
+                ));
+            }
+            if self.config.notifications_enabled {
+                notifications::session_finished(finished, &self.timer, || {});
+            }
+            self.sound.session_finished(finished, &self.config);
+        }
+        self.sound.update_ticking(timer.get_session_type(), timer.is_running(), &self.config);
+        record_finished_sessions(&mut timer, &mut self.tasks, self.history.as_ref());
+        if let Some(message) = goals::announce_reached(&mut timer, self.config.notifications_enabled).pop() {
+            self.message = Some(message);
+        }
+    }
+
+    fn shutdown(&mut self) {
+        let mut timer = self.timer.lock().unwrap();
+        record_finished_sessions(&mut timer, &mut self.tasks, self.history.as_ref());
+        drop(timer);
+        if self.pending_resume.is_none() {
+            self.save_snapshot();
+        }
+    }
+
+    fn save_snapshot(&mut self) {
+        self.last_snapshot = Instant::now();
+        let mut timer = self.timer.lock().unwrap();
+        if let Err(e) = SavedSession::store(&mut timer) {
+            self.message = Some(format!("Failed to save session state: {}", e));
+        }
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) {
+        if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
+            self.quit = true;
+            return;
+        }
+        self.message = None;
+        if self.pending_resume.is_some() {
+            self.handle_resume_key(key.code);
+        } else if self.picker.is_some() {
+            self.handle_picker_key(key.code);
+        } else {
+            self.handle_timer_key(key.code);
+        }
+    }
+
+    fn handle_resume_key(&mut self, code: KeyCode) {
+        match code {
+            KeyCode::Char('y') | KeyCode::Enter => {
+                let saved = self.pending_resume.take().unwrap();
+                self.timer.lock().unwrap().restore(&saved.timer, saved.time_away());
+                self.save_snapshot();
+            }
+            KeyCode::Char('n') | KeyCode::Esc => {
+                self.pending_resume = None;
+                if let Err(e) = SavedSession::clear() {
+                    self.message = Some(format!("Failed to discard session state: {}", e));
+                }
+            }
+            KeyCode::Char('q') => self.quit = true,
+            _ => {}
+        }
+    }
+
+    fn handle_timer_key(&mut self, code: KeyCode) {
+        let mut timer = self.timer.lock().unwrap();
+        match code {
+            KeyCode::Char(' ') | KeyCode::Char('p') => {
+                if timer.is_running() {
+                    timer.pause();
+                } else {
+                    timer.start();
+                }
+            }
+            KeyCode::Char('r') => timer.reset(),
+            KeyCode::Char('s') => timer.skip(),
+            KeyCode::Char('+') | KeyCode::Char('=') => timer.extend(crate::ADJUST_STEP),
+            KeyCode::Char('-') => timer.shorten(crate::ADJUST_STEP),
+            KeyCode::Char('v') => {
+                let in_overtime = timer.is_in_overtime();
+                let message = match (timer.void(None), in_overtime) {
+                    (true, true) => "Pomodoro already complete; counted it and moved on",
+                    (true, false) => "Pomodoro voided; it won't count",
+                    (false, _) => "Only a started work session can be voided",
+                };
+                self.message = Some(message.to_string());
+            }
+            KeyCode::Char(c @ ('i' | 'e')) => {
+                let kind = if c == 'i' { InterruptionKind::Internal } else { InterruptionKind::External };
+                self.message = Some(if timer.interrupt(kind, None, false) {
+                    format!("{:?} interruption logged", kind)
+                } else {
+                    "Interruptions are logged during work sessions".to_string()
+                });
+            }
+            KeyCode::Char('t') => {
+                // Same rule as the window's picker: don't split a running pomodoro
+                if timer.is_running() && timer.get_session_type() == SessionType::Work {
+                    self.message = Some("Pause the work session to switch tasks".to_string());
+                } else {
+                    let row = self.open_task_ids().iter().position(|&id| id == self.tasks.active().map(|t| t.id));
+                    self.picker = Some(ListState::default().with_selected(row));
+                }
+            }
+            KeyCode::Char('q') | KeyCode::Esc => self.quit = true,
+            _ => {}
+        }
+    }
+
+    fn handle_picker_key(&mut self, code: KeyCode) {
+        let ids = self.open_task_ids();
+        let Some(picker) = self.picker.as_mut() else {
+            return;
+        };
+        match code {
+            KeyCode::Up | KeyCode::Char('k') => picker.select_previous(),
+            KeyCode::Down | KeyCode::Char('j') => {
+                let next = picker.selected().map_or(0, |row| (row + 1).min(ids.len() - 1));
+                picker.select(Some(next));
+            }
+            KeyCode::Enter => {
+                let id = picker.selected().and_then(|row| ids.get(row).copied()).flatten();
+                self.picker = None;
+                self.set_active_task(id);
+            }
+            KeyCode::Esc | KeyCode::Char('q') => self.picker = None,
+            _ => {}
+        }
+    }
+
+    // The picker's rows: `None` for "No task", then each open task
+    fn open_task_ids(&self) -> Vec<Option<u64>> {
+        let open = self.tasks.tasks().iter().filter(|task| !task.completed);
+        std::iter::once(None).chain(open.map(|task| Some(task.id))).collect()
+    }
+
+    fn set_active_task(&mut self, id: Option<u64>) {
+        self.tasks.set_active(id);
+        self.timer.lock().unwrap().set_task(self.tasks.active().map(|task| task.tag()));
+        if let Err(e) = self.tasks.save() {
+            self.message = Some(format!("Failed to save tasks: {}", e));
+        }
+    }
+
+    fn draw(&mut self, frame: &mut Frame, status: &TimerStatus) {
+        let color = session_color(status.session_type);
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(" 🍅 Pomodoro Timer ")
+            .title_alignment(Alignment::Center)
+            .border_style(Style::default().fg(TOMATO));
+        let area = block.inner(frame.area());
+        frame.render_widget(block, frame.area());
+
+        let [_, session, _, digits, _, progress, cycle, _, task, _, footer] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Length(1),
+            Constraint::Length(1),
+            Constraint::Length(DIGIT_ROWS as u16),
+            Constraint::Length(1),
+            Constraint::Length(1),
+            Constraint::Length(1),
+            Constraint::Length(1),
+            Constraint::Length(1),
+            Constraint::Min(0),
+            Constraint::Length(1),
+        ])
+        .areas(area);
+
+        let state = if status.is_running {
+            String::new()
+        } else {
+            format!("  ({})", status.state())
+        };
+        let title = Line::from(vec![
+            Span::styled(status.session_type.name(), Style::default().fg(color).add_modifier(Modifier::BOLD)),
+            Span::raw(state),
+        ]);
+        frame.render_widget(Paragraph::new(title).alignment(Alignment::Center), session);
+
+        let lines: Vec<Line> = big_text(&status.time).into_iter().map(Line::from).collect();
+        frame.render_widget(
+            Paragraph::new(lines).style(Style::default().fg(color)).alignment(Alignment::Center),
+            digits,
+        );
+
+        let gauge = Gauge::default()
+            .gauge_style(Style::default().fg(color).bg(Color::DarkGray))
+            .ratio(status.progress.clamp(0.0, 1.0) as f64)
+            .label("");
+        frame.render_widget(gauge, centered(progress, 4));
+
+        frame.render_widget(Paragraph::new(cycle_line(status)).alignment(Alignment::Center), cycle);
+
+        let task_name = status.task.as_deref().unwrap_or("No task");
+        frame.render_widget(
+            Paragraph::new(format!("Task: {}", task_name)).alignment(Alignment::Center),
+            task,
+        );
+
+        let help = "space start/pause · r reset · s skip · +/- 5 min · i/e interruption · v void · t task · q quit";
+        let footer_text = self.message.as_deref().unwrap_or(help);
+        frame.render_widget(
+            Paragraph::new(footer_text).style(Style::default().fg(Color::Gray)).alignment(Alignment::Center),
+            footer,
+        );
+
+        if let Some(saved) = &self.pending_resume {
+            self.draw_resume_prompt(frame, saved);
+        } else if self.picker.is_some() {
+            self.draw_task_picker(frame);
+        }
+    }
+
+    fn draw_resume_prompt(&self, frame: &mut Frame, saved: &SavedSession) {
+        let remaining = saved.timer.time_remaining.as_secs();
+        let state = if saved.timer.is_running { "running" } else { "paused" };
+        let text = vec![
+            Line::from(format!(
+                "{} with {:02}:{:02} left ({})",
+                saved.timer.session_type.name(),
+                remaining / 60,
+                remaining % 60,
+                state,
+            )),
+            Line::from(format!("when the timer closed at {}.", saved.saved_at.format("%H:%M"))),
+            Line::from(""),
+            Line::from("y resume · n discard"),
+        ];
+        let area = popup(frame.area(), 44, text.len() as u16 + 2);
+        frame.render_widget(Clear, area);
+        frame.render_widget(
+            Paragraph::new(text)
+                .alignment(Alignment::Center)
+                .block(Block::default().borders(Borders::ALL).title(" Resume previous session? ")),
+            area,
+        );
+    }
+
+    fn draw_task_picker(&mut self, frame: &mut Frame) {
+        let mut items = vec![ListItem::new("No task")];
+        for task in self.tasks.tasks().iter().filter(|task| !task.completed) {
+            items.push(ListItem::new(format!(
+                "{}  ({}/{})",
+                task.name, task.actual_pomodoros, task.estimated_pomodoros
+            )));
+        }
+
+        let area = popup(frame.area(), 44, items.len() as u16 + 2);
+        let list = List::new(items)
+            .block(Block::default().borders(Borders::ALL).title(" Active task "))
+            .highlight_style(Style::default().fg(TOMATO).add_modifier(Modifier::BOLD))
+            .highlight_symbol("> ");
+        frame.render_widget(Clear, area);
+        if let Some(picker) = self.picker.as_mut() {
+            frame.render_stateful_widget(list, area, picker);
+        }
+    }
+}
+
+fn session_color(session_type: SessionType) -> Color {
+    match session_type {
+        SessionType::Work => TOMATO,
+        SessionType::ShortBreak => Color::Green,
+        SessionType::LongBreak => Color::Cyan,
+    }
+}
+
+/// Renders `text` (digits and colons) in the five-row countdown font.
+fn big_text(text: &str) -> [String; DIGIT_ROWS] {
+    let mut rows: [String; DIGIT_ROWS] = Default::default();
+    for (i, c) in text.chars().enumerate() {
+        let glyph = GLYPH_CHARS.find(c).map(|index| GLYPHS[index]);
+        for (row, line) in rows.iter_mut().enumerate() {
+            if i > 0 {
+                line.push(' ');
+            }
+            line.push_str(glyph.map_or("     ", |glyph| glyph[row]));
+        }
+    }
+    rows
+}
+
+// Filled dots for finished pomodoros in the cycle, a ring for the current one
+fn cycle_line(status: &TimerStatus) -> String {
+    let done = match status.session_type {
+        SessionType::Work => status.session.saturating_sub(1),
+        _ => status.session,
+    };
+    let dots: Vec<&str> = (0..status.cycle_length)
+        .map(|i| match i.cmp(&done) {
+            std::cmp::Ordering::Less => "●",
+            std::cmp::Ordering::Equal if status.session_type == SessionType::Work => "◉",
+            _ => "○",
+        })
+        .collect();
+    let goal = status.goal.map(|goal| format!("   goal {}", goal.summary())).unwrap_or_default();
+    format!("{}   session {} of {}{}", dots.join(" "), status.session, status.cycle_length, goal)
+}
+
+fn centered(area: Rect, margin: u16) -> Rect {
+    let margin = margin.min(area.width / 2);
+    Rect {
+        x: area.x + margin,
+        width: area.width - 2 * margin,
+        ..area
+    }
+}
+
+fn popup(area: Rect, width: u16, height: u16) -> Rect {
+    let width = width.min(area.width);
+    let height = height.min(area.height);
+    Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::ManualClock;
+    use std::sync::Arc;
+
+    #[test]
+    fn big_text_keeps_rows_aligned() {
+        let rows = big_text("25:00");
+        let width = rows[0].chars().count();
+        assert_eq!(width, 5 * 4 + 3 + 4);
+        assert!(rows.iter().all(|row| row.chars().count() == width));
+        assert_eq!(rows[2], "█████ █████     █   █ █   █");
+    }
+
+    #[test]
+    fn overtime_clock_shows_a_plus() {
+        let clock = ManualClock::new();
+        let mut timer = PomodoroTimer::with_clock(Arc::new(clock.clone()));
+        timer.set_flow_mode(true, false);
+        timer.start();
+        clock.advance(Duration::from_secs(25 * 60 + 192));
+        let status = timer.status();
+        assert_eq!(status.time, "+03:12");
+
+        let rows = big_text(&status.time);
+        assert!(rows.iter().all(|row| row.chars().count() == rows[0].chars().count()));
+        assert!(rows[2].starts_with("███ █   █ █████"));
+    }
+}